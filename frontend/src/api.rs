@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, Utc};
 use gloo_net::http::{Request, Response};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -14,6 +14,17 @@ pub enum ApiError {
     HttpNotFound(String),
     GenericError(String),
     JsonError,
+    NotEnoughStock(String),
+    PurchaseAlreadyPaid(String),
+    BulkCountMismatch(String),
+    DuplicateUser(String),
+    /// Quantity is zero or above the maximum per purchase
+    InvalidQuantity {
+        message: String,
+        max: u32,
+    },
+    /// Lines of a checkout that couldn't be bought
+    CheckoutFailed(Vec<CheckoutError>),
 }
 
 impl Display for ApiError {
@@ -27,6 +38,12 @@ impl Display for ApiError {
             ApiError::HttpNotFound(msg) => write!(f, "Not found: {}", msg),
             ApiError::GenericError(msg) => write!(f, "Error: {}", msg),
             ApiError::JsonError => write!(f, "Failed to parse data from server"),
+            ApiError::NotEnoughStock(msg)
+            | ApiError::PurchaseAlreadyPaid(msg)
+            | ApiError::BulkCountMismatch(msg)
+            | ApiError::DuplicateUser(msg) => write!(f, "Error: {}", msg),
+            ApiError::InvalidQuantity { message, .. } => write!(f, "Invalid data: {}", message),
+            ApiError::CheckoutFailed(_) => write!(f, "Error: some items can't be bought"),
         }
     }
 }
@@ -37,6 +54,20 @@ impl From<gloo_net::Error> for ApiError {
     }
 }
 
+/// Body of the server's error responses
+#[derive(Deserialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+    #[serde(default)]
+    details: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct InvalidQuantityDetails {
+    max: u32,
+}
+
 async fn handle_response<T: DeserializeOwned>(response: Response) -> Result<T, ApiError> {
     if response.ok() {
         response.json().await.map_err(|_| ApiError::JsonError)
@@ -54,17 +85,41 @@ async fn handle_blank_response(response: Response) -> Result<(), ApiError> {
 }
 
 async fn error_from_response(response: &Response) -> ApiError {
-    let error_message = response.text().await;
-    match error_message {
-        Ok(error_message) => match response.status() {
-            400 => ApiError::HttpBadRequest(error_message),
-            401 => ApiError::HttpUnauthorized(error_message),
-            403 => ApiError::HttpForbidden(error_message),
-            404 => ApiError::HttpNotFound(error_message),
-            409 => ApiError::HttpConflict(error_message),
-            _ => ApiError::GenericError(error_message),
-        },
-        Err(_) => ApiError::GenericError("failed to parse response text".to_string()),
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(_) => return ApiError::GenericError("failed to parse response text".to_string()),
+    };
+    // errors that don't come from our handlers, e.g. a rejected request body, are plain text
+    let error: ErrorBody = match serde_json::from_str(&body) {
+        Ok(error) => error,
+        Err(_) => return error_from_status(response.status(), body),
+    };
+    match error.code.as_str() {
+        "not_enough_stock" => ApiError::NotEnoughStock(error.message),
+        "purchase_already_paid" => ApiError::PurchaseAlreadyPaid(error.message),
+        "bulk_count_mismatch" => ApiError::BulkCountMismatch(error.message),
+        "duplicate_user" => ApiError::DuplicateUser(error.message),
+        "invalid_quantity" => serde_json::from_value(error.details).map_or(
+            ApiError::JsonError,
+            |details: InvalidQuantityDetails| ApiError::InvalidQuantity {
+                message: error.message,
+                max: details.max,
+            },
+        ),
+        "checkout_failed" => serde_json::from_value(error.details)
+            .map_or(ApiError::JsonError, ApiError::CheckoutFailed),
+        _ => error_from_status(response.status(), error.message),
+    }
+}
+
+fn error_from_status(status: u16, message: String) -> ApiError {
+    match status {
+        400 => ApiError::HttpBadRequest(message),
+        401 => ApiError::HttpUnauthorized(message),
+        403 => ApiError::HttpForbidden(message),
+        404 => ApiError::HttpNotFound(message),
+        409 => ApiError::HttpConflict(message),
+        _ => ApiError::GenericError(message),
     }
 }
 
@@ -77,10 +132,106 @@ pub struct Product {
     pub seller_name: String,
     pub price: u32,
     pub stock: u32,
+    pub archived: bool,
+    pub category_id: Option<u32>,
+    pub barcode: Option<String>,
+    pub image_url: Option<String>,
+    pub thumbnail_url: Option<String>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ProductSort {
+    #[default]
+    Stock,
+    Price,
+    Name,
+    Newest,
+    Popularity,
+}
+
+impl ProductSort {
+    pub const ALL: [ProductSort; 5] = [
+        ProductSort::Stock,
+        ProductSort::Price,
+        ProductSort::Name,
+        ProductSort::Newest,
+        ProductSort::Popularity,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ProductSort::Stock => "stock",
+            ProductSort::Price => "price",
+            ProductSort::Name => "name",
+            ProductSort::Newest => "newest",
+            ProductSort::Popularity => "popularity",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        ProductSort::ALL
+            .into_iter()
+            .find(|sort| sort.key() == key)
+            .unwrap_or_default()
+    }
+}
+
+impl Display for ProductSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            ProductSort::Stock => write!(f, "Stock"),
+            ProductSort::Price => write!(f, "Price"),
+            ProductSort::Name => write!(f, "Name"),
+            ProductSort::Newest => write!(f, "Newest"),
+            ProductSort::Popularity => write!(f, "Popularity"),
+        }
+    }
 }
 
-pub async fn list_products() -> Result<Vec<Product>, ApiError> {
-    let resp = Request::get("/api/products").send().await?;
+#[derive(Clone, Default, PartialEq)]
+pub struct ProductQuery {
+    pub search: Option<String>,
+    pub seller_id: Option<u32>,
+    pub sort: ProductSort,
+    pub include_out_of_stock: bool,
+}
+
+pub async fn list_products(query: &ProductQuery) -> Result<Vec<Product>, ApiError> {
+    let mut params = vec![("sort", query.sort.key().to_string())];
+    if let Some(search) = &query.search {
+        params.push(("search", search.clone()));
+    }
+    if let Some(seller_id) = query.seller_id {
+        params.push(("seller", seller_id.to_string()));
+    }
+    if query.include_out_of_stock {
+        params.push(("include_out_of_stock", true.to_string()));
+    }
+
+    let resp = Request::get("/api/products").query(params).send().await?;
+
+    handle_response(resp).await
+}
+
+pub async fn list_archived_products() -> Result<Vec<Product>, ApiError> {
+    let resp = Request::get("/api/products/archived").send().await?;
+
+    handle_response(resp).await
+}
+
+pub async fn get_product(product_id: u32) -> Result<Product, ApiError> {
+    let resp = Request::get(&format!("/api/product/{}", product_id))
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+/// Finds the product in the catalog with the given EAN-13 barcode
+pub async fn get_product_by_barcode(barcode: &str) -> Result<Product, ApiError> {
+    let resp = Request::get(&format!("/api/products/barcode/{}", barcode))
+        .send()
+        .await?;
 
     handle_response(resp).await
 }
@@ -90,8 +241,39 @@ pub struct ProductPayload {
     pub id: Option<u32>,
     pub name: String,
     pub description: Option<String>,
-    pub stock: u32,
+    /// Only sent when inserting, stock is changed by restocking or adjusting it afterwards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stock: Option<u32>,
     pub price: u32,
+    pub category_id: Option<u32>,
+    pub barcode: Option<String>,
+    pub price_change_reason: Option<String>,
+}
+
+/// Uploads a PNG or JPEG image for the product, replacing the previous one
+pub async fn upload_product_image(
+    product_id: u32,
+    image: web_sys::File,
+) -> Result<Product, ApiError> {
+    let resp = Request::post(&format!("/api/product/{}/image", product_id))
+        .header("Content-Type", &image.type_())
+        .body(image)
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Category {
+    pub id: u32,
+    pub name: String,
+}
+
+pub async fn list_categories() -> Result<Vec<Category>, ApiError> {
+    let resp = Request::get("/api/categories").send().await?;
+
+    handle_response(resp).await
 }
 
 pub async fn insert_product(product: &ProductPayload) -> Result<Product, ApiError> {
@@ -104,6 +286,89 @@ pub async fn insert_product(product: &ProductPayload) -> Result<Product, ApiErro
     handle_response(resp).await
 }
 
+pub async fn update_product(
+    product_id: u32,
+    product: &ProductPayload,
+) -> Result<Product, ApiError> {
+    let resp = Request::put(&format!("/api/product/{}", product_id))
+        .json(product)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct PriceHistoryEntry {
+    pub old_price: u32,
+    pub new_price: u32,
+    pub date: DateTime<Local>,
+    pub actor_name: String,
+    pub reason: Option<String>,
+}
+
+pub async fn get_price_history(product_id: u32) -> Result<Vec<PriceHistoryEntry>, ApiError> {
+    let resp = Request::get(&format!("/api/product/{}/history", product_id))
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Clone, Serialize)]
+pub struct RestockProductPayload {
+    pub quantity: u32,
+}
+
+pub async fn restock_product(
+    product_id: u32,
+    payload: &RestockProductPayload,
+) -> Result<Product, ApiError> {
+    let resp = Request::post(&format!("/api/product/{}/restock", product_id))
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Clone, Serialize)]
+pub struct AdjustStockPayload {
+    /// Items to add, or to remove when negative
+    pub delta: i64,
+}
+
+pub async fn adjust_stock(
+    product_id: u32,
+    payload: &AdjustStockPayload,
+) -> Result<Product, ApiError> {
+    let resp = Request::post(&format!("/api/product/{}/stock", product_id))
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+pub async fn archive_product(product_id: u32) -> Result<Product, ApiError> {
+    let resp = Request::post(&format!("/api/product/{}/archive", product_id))
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+pub async fn unarchive_product(product_id: u32) -> Result<Product, ApiError> {
+    let resp = Request::post(&format!("/api/product/{}/unarchive", product_id))
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
 #[derive(Clone, Serialize)]
 pub struct PurchaseProductPayload {
     pub quantity: u32,
@@ -112,14 +377,43 @@ pub struct PurchaseProductPayload {
 pub async fn purchase_product(
     product_id: u32,
     payload: &PurchaseProductPayload,
-) -> Result<(), ApiError> {
+) -> Result<Purchase, ApiError> {
     let resp = Request::post(&format!("/api/product/{}/purchase", product_id))
         .json(payload)
         .expect("payload must be serializable to json")
         .send()
         .await?;
 
-    handle_blank_response(resp).await
+    handle_response(resp).await
+}
+
+#[derive(Clone, Serialize)]
+pub struct CheckoutItemPayload {
+    pub product_id: u32,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CheckoutPayload {
+    pub items: Vec<CheckoutItemPayload>,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct CheckoutError {
+    pub product_id: u32,
+    pub message: String,
+    pub stock: Option<u32>,
+}
+
+/// Buys every item at once, either all of them are bought or none is
+pub async fn checkout(payload: &CheckoutPayload) -> Result<Vec<Purchase>, ApiError> {
+    let resp = Request::post("/api/checkout")
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
 }
 
 #[derive(Serialize, Deserialize)]
@@ -143,6 +437,48 @@ pub struct User {
     pub id: u32,
     pub name: String,
     pub phone_number: String,
+    #[serde(default)]
+    pub role: UserRole,
+    #[serde(default)]
+    pub disabled: bool,
+    /// Total credit with all sellers, only present for the logged in user
+    #[serde(default)]
+    pub credit: Option<i64>,
+}
+
+#[derive(Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    #[default]
+    User,
+    Admin,
+}
+
+impl UserRole {
+    pub const ALL: [UserRole; 2] = [UserRole::User, UserRole::Admin];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            UserRole::User => "user",
+            UserRole::Admin => "admin",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        UserRole::ALL
+            .into_iter()
+            .find(|role| role.key() == key)
+            .unwrap_or(UserRole::User)
+    }
+}
+
+impl Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            UserRole::User => write!(f, "User"),
+            UserRole::Admin => write!(f, "Admin"),
+        }
+    }
 }
 
 pub async fn user_info() -> Result<User, ApiError> {
@@ -151,12 +487,176 @@ pub async fn user_info() -> Result<User, ApiError> {
     handle_response(resp).await
 }
 
+/// Fields left as `None` aren't changed
+#[derive(Serialize)]
+pub struct UpdateUserInfoPayload {
+    pub name: Option<String>,
+    pub phone_number: Option<String>,
+}
+
+pub async fn update_user_info(payload: &UpdateUserInfoPayload) -> Result<User, ApiError> {
+    let resp = Request::patch("/api/user/info")
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
 pub async fn logout() -> Result<(), ApiError> {
     let resp = Request::get("/api/logout").send().await?;
 
     handle_blank_response(resp).await
 }
 
+#[derive(Serialize)]
+pub struct ChangePasswordPayload {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+pub async fn change_password(payload: &ChangePasswordPayload) -> Result<(), ApiError> {
+    let resp = Request::post("/api/user/password")
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_blank_response(resp).await
+}
+
+#[derive(Serialize)]
+pub struct ResetPasswordPayload {
+    pub token: String,
+    pub password: String,
+}
+
+pub async fn reset_password(payload: &ResetPasswordPayload) -> Result<(), ApiError> {
+    let resp = Request::post("/api/password-reset")
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_blank_response(resp).await
+}
+
+#[derive(Serialize)]
+pub struct RegisterPayload {
+    pub name: String,
+    pub phone_number: String,
+    pub password: String,
+    pub invite_code: String,
+}
+
+pub async fn register(payload: &RegisterPayload) -> Result<User, ApiError> {
+    let resp = Request::post("/api/register")
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Invite {
+    pub code: String,
+    pub created_date: DateTime<Local>,
+    pub expiry_date: DateTime<Local>,
+    pub used_by: Option<User>,
+    pub used_date: Option<DateTime<Local>>,
+}
+
+pub async fn list_invites() -> Result<Vec<Invite>, ApiError> {
+    let resp = Request::get("/api/invites").send().await?;
+
+    handle_response(resp).await
+}
+
+pub async fn create_invite() -> Result<Invite, ApiError> {
+    let resp = Request::post("/api/invite").send().await?;
+
+    handle_response(resp).await
+}
+
+pub async fn admin_list_users() -> Result<Vec<User>, ApiError> {
+    let resp = Request::get("/api/admin/users").send().await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Serialize)]
+pub struct AdminCreateUserPayload {
+    pub name: String,
+    pub phone_number: String,
+    pub password: String,
+    pub role: UserRole,
+}
+
+pub async fn admin_create_user(payload: &AdminCreateUserPayload) -> Result<User, ApiError> {
+    let resp = Request::post("/api/admin/user")
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Serialize)]
+pub struct AdminUpdateUserPayload {
+    pub name: String,
+    pub role: UserRole,
+    pub disabled: bool,
+}
+
+pub async fn admin_update_user(
+    user_id: u32,
+    payload: &AdminUpdateUserPayload,
+) -> Result<User, ApiError> {
+    let resp = Request::put(&format!("/api/admin/user/{}", user_id))
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Serialize)]
+pub struct AdminResetPasswordPayload {
+    pub password: String,
+}
+
+pub async fn admin_reset_password(
+    user_id: u32,
+    payload: &AdminResetPasswordPayload,
+) -> Result<User, ApiError> {
+    let resp = Request::post(&format!("/api/admin/user/{}/password", user_id))
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct PasswordReset {
+    pub token: String,
+    pub expiry_date: DateTime<Local>,
+}
+
+pub async fn admin_create_password_reset(user_id: u32) -> Result<PasswordReset, ApiError> {
+    let resp = Request::post(&format!("/api/admin/user/{}/password-reset", user_id))
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct Purchase {
     pub id: u32,
@@ -166,17 +666,97 @@ pub struct Purchase {
     pub unit_price: u32,
     pub date: DateTime<Local>,
     pub paid_date: Option<DateTime<Local>>,
+    pub refunds: Vec<Refund>,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Refund {
+    pub id: u32,
+    pub amount: u32,
+    pub reason: String,
+    pub returned_quantity: u32,
+    pub date: DateTime<Local>,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct BuyerGroupedPurchases {
     pub buyer: User,
     pub amount_due: u32,
+    pub credit: i64,
     pub purchases: Vec<Purchase>,
 }
 
-pub async fn list_purchases() -> Result<Vec<Purchase>, ApiError> {
-    let resp = Request::get("/api/purchases/history").send().await?;
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct SellerGroupedPurchases {
+    pub seller: User,
+    pub amount_due: u32,
+    pub credit: i64,
+    pub purchases: Vec<Purchase>,
+}
+
+/// Filters for the purchase history, unset fields don't filter anything
+#[derive(Clone, Default, PartialEq)]
+pub struct PurchaseFilter {
+    /// Only purchases made on or after this date
+    pub from: Option<DateTime<Local>>,
+    /// Only purchases made before this date
+    pub to: Option<DateTime<Local>>,
+    pub product_id: Option<u32>,
+    pub seller_id: Option<u32>,
+    pub paid: Option<bool>,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct PurchaseCursor {
+    before_date: DateTime<Local>,
+    before_id: u32,
+}
+
+#[derive(Deserialize)]
+pub struct PurchaseHistoryPage {
+    pub purchases: Vec<Purchase>,
+    pub next_cursor: Option<PurchaseCursor>,
+}
+
+fn format_query_date(date: DateTime<Local>) -> String {
+    date.with_timezone(&Utc).to_rfc3339()
+}
+
+pub async fn list_purchases(
+    filter: &PurchaseFilter,
+    cursor: Option<&PurchaseCursor>,
+) -> Result<PurchaseHistoryPage, ApiError> {
+    let mut params = Vec::new();
+    if let Some(cursor) = cursor {
+        params.push(("before_date", format_query_date(cursor.before_date)));
+        params.push(("before_id", cursor.before_id.to_string()));
+    }
+    if let Some(from) = filter.from {
+        params.push(("from", format_query_date(from)));
+    }
+    if let Some(to) = filter.to {
+        params.push(("to", format_query_date(to)));
+    }
+    if let Some(product_id) = filter.product_id {
+        params.push(("product", product_id.to_string()));
+    }
+    if let Some(seller_id) = filter.seller_id {
+        params.push(("seller", seller_id.to_string()));
+    }
+    if let Some(paid) = filter.paid {
+        params.push(("paid", paid.to_string()));
+    }
+
+    let resp = Request::get("/api/purchases/history")
+        .query(params)
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+pub async fn buyer_summary() -> Result<Vec<SellerGroupedPurchases>, ApiError> {
+    let resp = Request::get("/api/purchases/buyer-summary").send().await?;
 
     handle_response(resp).await
 }
@@ -187,27 +767,187 @@ pub async fn seller_summary() -> Result<Vec<BuyerGroupedPurchases>, ApiError> {
     handle_response(resp).await
 }
 
-pub async fn pay_purchase(purchase_id: u32) -> Result<(), ApiError> {
+pub async fn cancel_purchase(purchase_id: u32) -> Result<(), ApiError> {
+    let resp = Request::delete(&format!("/api/purchase/{}", purchase_id))
+        .send()
+        .await?;
+
+    handle_blank_response(resp).await
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Cash,
+    BankTransfer,
+    MbWay,
+    Other,
+    Credit,
+}
+
+impl PaymentMethod {
+    pub const ALL: [PaymentMethod; 4] = [
+        PaymentMethod::Cash,
+        PaymentMethod::BankTransfer,
+        PaymentMethod::MbWay,
+        PaymentMethod::Other,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            PaymentMethod::Cash => "cash",
+            PaymentMethod::BankTransfer => "bank_transfer",
+            PaymentMethod::MbWay => "mb_way",
+            PaymentMethod::Other => "other",
+            PaymentMethod::Credit => "credit",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        PaymentMethod::ALL
+            .into_iter()
+            .find(|method| method.key() == key)
+            .unwrap_or(PaymentMethod::Other)
+    }
+}
+
+impl Display for PaymentMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            PaymentMethod::Cash => write!(f, "Cash"),
+            PaymentMethod::BankTransfer => write!(f, "Bank Transfer"),
+            PaymentMethod::MbWay => write!(f, "MB WAY"),
+            PaymentMethod::Other => write!(f, "Other"),
+            PaymentMethod::Credit => write!(f, "Credit"),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Settlement {
+    pub id: u32,
+    pub seller: User,
+    pub buyer: User,
+    pub amount: u32,
+    pub payment_method: PaymentMethod,
+    pub credit_used: u32,
+    pub date: DateTime<Local>,
+    pub purchases: Option<Vec<Purchase>>,
+}
+
+pub async fn pay_purchase(purchase_id: u32) -> Result<Settlement, ApiError> {
     let resp = Request::post(&format!("/api/purchase/{}/pay", purchase_id))
         .send()
         .await?;
 
+    handle_response(resp).await
+}
+
+pub async fn unpay_purchase(purchase_id: u32) -> Result<(), ApiError> {
+    let resp = Request::post(&format!("/api/purchase/{}/unpay", purchase_id))
+        .send()
+        .await?;
+
     handle_blank_response(resp).await
 }
 
+#[derive(Clone, Serialize)]
+pub struct RefundPurchasePayload {
+    pub amount: u32,
+    pub reason: String,
+    pub returned_quantity: Option<u32>,
+}
+
+pub async fn refund_purchase(
+    purchase_id: u32,
+    payload: &RefundPurchasePayload,
+) -> Result<Purchase, ApiError> {
+    let resp = Request::post(&format!("/api/purchase/{}/refund", purchase_id))
+        .json(payload)
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
 #[derive(Serialize)]
 struct PayPurchaseUserBulkPayload {
     count: u32,
+    payment_method: PaymentMethod,
 }
 
-pub async fn pay_purchase_user_bulk(buyer_id: u32, purchase_count: u32) -> Result<(), ApiError> {
+pub async fn pay_purchase_user_bulk(
+    buyer_id: u32,
+    purchase_count: u32,
+    payment_method: PaymentMethod,
+) -> Result<Settlement, ApiError> {
     let resp = Request::post(&format!("/api/purchase/user/{}/pay", buyer_id))
         .json(&PayPurchaseUserBulkPayload {
             count: purchase_count,
+            payment_method,
+        })
+        .expect("payload must be serializable to json")
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+#[derive(Serialize)]
+struct RecordPaymentPayload {
+    amount: u32,
+    payment_method: PaymentMethod,
+}
+
+/// Records a payment from the buyer, returning the settlement of the purchases it covered, if any
+pub async fn record_payment(
+    buyer_id: u32,
+    amount: u32,
+    payment_method: PaymentMethod,
+) -> Result<Option<Settlement>, ApiError> {
+    let resp = Request::post(&format!("/api/purchase/user/{}/payment", buyer_id))
+        .json(&RecordPaymentPayload {
+            amount,
+            payment_method,
         })
         .expect("payload must be serializable to json")
         .send()
         .await?;
 
+    handle_response(resp).await
+}
+
+pub async fn list_settlements() -> Result<Vec<Settlement>, ApiError> {
+    let resp = Request::get("/api/settlements").send().await?;
+
+    handle_response(resp).await
+}
+
+pub async fn get_settlement(settlement_id: u32) -> Result<Settlement, ApiError> {
+    let resp = Request::get(&format!("/api/settlement/{}", settlement_id))
+        .send()
+        .await?;
+
+    handle_response(resp).await
+}
+
+pub async fn revert_settlement(settlement_id: u32) -> Result<(), ApiError> {
+    let resp = Request::post(&format!("/api/settlement/{}/revert", settlement_id))
+        .send()
+        .await?;
+
     handle_blank_response(resp).await
 }
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Wallet {
+    pub seller: User,
+    pub balance: i64,
+}
+
+pub async fn list_wallets() -> Result<Vec<Wallet>, ApiError> {
+    let resp = Request::get("/api/wallets").send().await?;
+
+    handle_response(resp).await
+}
@@ -1,5 +1,6 @@
 pub mod product;
 pub mod purchase;
+pub mod user;
 
 pub mod dialog;
 pub mod footer;
@@ -1,11 +1,19 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
 
-use crate::{utils::class_if, Route};
+use crate::{
+    api,
+    hooks::auth::use_auth,
+    utils::{self, class_if},
+    Route,
+};
 
 #[function_component(Navbar)]
 pub fn navbar() -> Html {
     let active_route = use_route::<Route>().unwrap_or_default();
+    let user = use_auth();
+    let credit = user.as_ref().and_then(|user| user.credit).unwrap_or(0);
+    let is_admin = user.map_or(false, |user| user.role == api::UserRole::Admin);
 
     html! {
         <header class="navbar">
@@ -15,7 +23,31 @@ pub fn navbar() -> Html {
             <div class="nav-links">
                 <Link<Route> to={Route::ProductPage} classes={classes!(class_if(active_route == Route::ProductPage, "active"))}>{"Products"}</Link<Route>>
                 <Link<Route> to={Route::PurchasesPage} classes={classes!(class_if(active_route == Route::PurchasesPage, "active"))}>{"Purchases"}</Link<Route>>
+                <Link<Route> to={Route::SettlementsPage} classes={classes!(class_if(active_route == Route::SettlementsPage, "active"))}>{"Settlements"}</Link<Route>>
+                <Link<Route> to={Route::InvitesPage} classes={classes!(class_if(active_route == Route::InvitesPage, "active"))}>{"Invites"}</Link<Route>>
+                <Link<Route> to={Route::ProfilePage} classes={classes!(class_if(active_route == Route::ProfilePage, "active"))}>{"Profile"}</Link<Route>>
+                <Link<Route> to={Route::SettingsPage} classes={classes!(class_if(active_route == Route::SettingsPage, "active"))}>{"Settings"}</Link<Route>>
+                {
+                    if is_admin {
+                        html! {
+                            <Link<Route> to={Route::AdminPage} classes={classes!(class_if(active_route == Route::AdminPage, "active"))}>{"Admin"}</Link<Route>>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
             </div>
+            {
+                if credit > 0 {
+                    html! {
+                        <div class="nav-credit">
+                            {format!("Credit: {}", utils::format_display_balance(credit))}
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
         </header>
     }
 }
@@ -0,0 +1,58 @@
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{api, utils};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct ArchivedProductItemProps {
+    pub product: api::Product,
+    pub on_update: Callback<()>,
+}
+
+#[function_component(ArchivedProductItem)]
+pub fn archived_product_item(props: &ArchivedProductItemProps) -> Html {
+    let unarchive = {
+        let on_update = props.on_update.clone();
+        let product_id = props.product.id;
+        use_async(async move {
+            let res = api::unarchive_product(product_id).await;
+            if res.is_ok() {
+                on_update.emit(());
+            }
+            res
+        })
+    };
+
+    let handle_unarchive = {
+        let unarchive = unarchive.clone();
+        Callback::from(move |_| {
+            unarchive.run();
+        })
+    };
+
+    let product = &props.product;
+
+    html! {
+        <div class="product-item">
+            <div class="product-info">
+                <div class="product-info--name">{product.name.clone()}</div>
+                <div class="product-info--metadata">
+                    <span class="product-info--stock">{product.stock}</span>
+                    {" in stock"}
+                </div>
+                <div class="product-info--description">{product.description.as_ref().unwrap_or(&String::new())}</div>
+            </div>
+            <div class="product-price">
+                {utils::format_display_price(product.price)}
+            </div>
+            <div class="product-actions">
+                <button onclick={handle_unarchive} disabled={unarchive.loading} class="btn product-actions--unarchive">{"Unarchive"}</button>
+                {
+                    unarchive.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="product-actions--error">{error}</div>
+                    })
+                }
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,90 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{
+    api::{self, ApiError},
+    components::product::product_purchase_flow::ProductPurchaseFlow,
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct BarcodeScannerProps {
+    pub on_update: Callback<()>,
+}
+
+/// Barcode entry box that opens the purchase dialog of the matching product.
+/// USB barcode scanners type the code followed by Enter, which submits the form.
+#[function_component(BarcodeScanner)]
+pub fn barcode_scanner(props: &BarcodeScannerProps) -> Html {
+    let barcode_ref = use_node_ref();
+    let scanned = use_state(|| None::<api::Product>);
+
+    let lookup = {
+        let barcode_ref = barcode_ref.clone();
+        let scanned = scanned.clone();
+        use_async(async move {
+            let input = barcode_ref.cast::<HtmlInputElement>().unwrap();
+            let barcode = input.value().trim().to_string();
+            if barcode.len() != 13 || !barcode.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ApiError::GenericError(
+                    "barcode must have 13 digits".to_string(),
+                ));
+            }
+            let res = api::get_product_by_barcode(&barcode).await;
+            if let Ok(product) = &res {
+                input.set_value("");
+                scanned.set(Some(product.clone()));
+            }
+            res
+        })
+    };
+
+    let handle_submit = {
+        let lookup = lookup.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // prevent form submission
+            lookup.run();
+        })
+    };
+
+    let purchase_close_handler = {
+        let scanned = scanned.clone();
+        let barcode_ref = barcode_ref.clone();
+        Callback::from(move |_| {
+            scanned.set(None);
+            // ready for the next scan
+            let _ = barcode_ref.cast::<HtmlInputElement>().unwrap().focus();
+        })
+    };
+
+    html! {
+        <>
+            <form class="barcode-scanner" onsubmit={handle_submit}>
+                <input
+                    ref={barcode_ref}
+                    type="text"
+                    inputmode="numeric"
+                    autocomplete="off"
+                    placeholder="Scan or type a barcode"
+                    disabled={lookup.loading}
+                />
+                <button type="submit" disabled={lookup.loading} class="btn">{"Find"}</button>
+                {
+                    lookup.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="barcode-scanner--error">{error}</div>
+                    })
+                }
+            </form>
+            {
+                scanned.as_ref().map_or_else(|| html!{}, |product| html! {
+                    <ProductPurchaseFlow
+                        key={product.id}
+                        product={product.clone()}
+                        on_close={purchase_close_handler}
+                        on_update={&props.on_update}
+                    />
+                })
+            }
+        </>
+    }
+}
@@ -0,0 +1,153 @@
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{
+    api::{self, ApiError},
+    utils::{self, class_if},
+};
+
+#[derive(Clone, PartialEq)]
+pub struct CartItem {
+    pub product: api::Product,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct CartProps {
+    pub items: Vec<CartItem>,
+    pub on_change: Callback<Vec<CartItem>>,
+    pub on_checkout: Callback<()>,
+}
+
+#[function_component(Cart)]
+pub fn cart(props: &CartProps) -> Html {
+    let checkout = {
+        let items = props.items.clone();
+        let on_change = props.on_change.clone();
+        let on_checkout = props.on_checkout.clone();
+        use_async(async move {
+            let payload = api::CheckoutPayload {
+                items: items
+                    .iter()
+                    .map(|item| api::CheckoutItemPayload {
+                        product_id: item.product.id,
+                        quantity: item.quantity,
+                    })
+                    .collect(),
+            };
+            let res = api::checkout(&payload).await;
+            if res.is_ok() {
+                on_change.emit(Vec::new());
+                on_checkout.emit(());
+            }
+            res
+        })
+    };
+
+    let handle_checkout = {
+        let checkout = checkout.clone();
+        Callback::from(move |_| {
+            checkout.run();
+        })
+    };
+
+    if props.items.is_empty() {
+        return match &checkout.data {
+            Some(purchases) => html! {
+                <div class="card products-card cart-card">
+                    <div class="card-header">
+                        {"Cart"}
+                    </div>
+                    <div class="card-content">
+                        <p>{format!("Purchase complete! {} item(s) bought.", purchases.len())}</p>
+                    </div>
+                </div>
+            },
+            None => html! {},
+        };
+    }
+
+    let line_errors: &[api::CheckoutError] = match &checkout.error {
+        Some(ApiError::CheckoutFailed(errors)) => errors.as_slice(),
+        _ => &[],
+    };
+    let general_error = checkout
+        .error
+        .as_ref()
+        .filter(|error| !matches!(error, ApiError::CheckoutFailed(_)));
+
+    let total: u32 = props
+        .items
+        .iter()
+        .map(|item| item.product.price * item.quantity)
+        .sum();
+
+    html! {
+        <div class={classes!("card", "products-card", "cart-card", class_if(checkout.loading, "card-loading"))}>
+            <div class="loading-bar" />
+            {
+                general_error.map_or_else(|| html!{}, |error| html! {
+                    <div class="card-error">{error}</div>
+                })
+            }
+            <div class="card-header">
+                {"Cart"}
+            </div>
+            <div class="card-content">
+                {
+                    props.items.iter().enumerate()
+                        .map(|(index, item)| {
+                            let set_quantity = |quantity: u32| {
+                                let items = props.items.clone();
+                                let on_change = props.on_change.clone();
+                                Callback::from(move |_| {
+                                    let mut items = items.clone();
+                                    if quantity == 0 {
+                                        items.remove(index);
+                                    } else {
+                                        items[index].quantity = quantity;
+                                    }
+                                    on_change.emit(items);
+                                })
+                            };
+                            let error = line_errors
+                                .iter()
+                                .find(|error| error.product_id == item.product.id);
+                            html! {
+                                <div key={item.product.id} class="cart-item">
+                                    <div class="cart-item--name">{item.product.name.clone()}</div>
+                                    <div class="cart-item--quantity">
+                                        <button onclick={set_quantity(item.quantity - 1)} disabled={checkout.loading} class="btn">{"-"}</button>
+                                        <span>{item.quantity}</span>
+                                        <button onclick={set_quantity(item.quantity + 1)} disabled={checkout.loading || item.quantity >= item.product.stock} class="btn">{"+"}</button>
+                                    </div>
+                                    <div class="cart-item--price">
+                                        {utils::format_display_price(item.product.price * item.quantity)}
+                                    </div>
+                                    <button onclick={set_quantity(0)} disabled={checkout.loading} class="btn cart-item--remove">{"Remove"}</button>
+                                    {
+                                        error.map_or_else(|| html!{}, |error| html! {
+                                            <div class="cart-item--error">
+                                                {
+                                                    match error.stock {
+                                                        Some(stock) => format!("{} (only {} left)", error.message, stock),
+                                                        None => error.message.clone(),
+                                                    }
+                                                }
+                                            </div>
+                                        })
+                                    }
+                                </div>
+                            }
+                        })
+                        .collect::<Html>()
+                }
+                <div class="cart-total">
+                    {"Total: "}
+                    <span class="cart-total--price">{utils::format_display_price(total)}</span>
+                </div>
+                <button onclick={handle_checkout} disabled={checkout.loading} class="btn btn--full-width cart-checkout">{"Checkout"}</button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,39 @@
+use yew::prelude::*;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+
+use crate::api;
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct CategorySelectProps {
+    pub select_ref: NodeRef,
+    #[prop_or_default]
+    pub selected: Option<u32>,
+}
+
+/// Select with all product categories, the empty value meaning no category
+#[function_component(CategorySelect)]
+pub fn category_select(props: &CategorySelectProps) -> Html {
+    let categories = use_async_with_options(
+        async move { api::list_categories().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    html! {
+        <select ref={props.select_ref.clone()} id="product--category">
+            <option value="" selected={props.selected.is_none()}>{"None"}</option>
+            {
+                categories.data.iter()
+                    .flatten()
+                    .map(|category| html! {
+                        <option
+                            value={category.id.to_string()}
+                            selected={props.selected == Some(category.id)}
+                        >
+                            {category.name.clone()}
+                        </option>
+                    })
+                    .collect::<Html>()
+            }
+        </select>
+    }
+}
@@ -1,3 +1,9 @@
+pub mod archived_product_item;
+pub mod barcode_scanner;
+pub mod cart;
+pub mod category_select;
 pub mod product_item;
+pub mod product_price_history_dialog;
 pub mod product_purchase_complete_dialog;
 pub mod product_purchase_dialog;
+pub mod product_purchase_flow;
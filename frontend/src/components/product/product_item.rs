@@ -1,56 +1,72 @@
-use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
+use yew_hooks::use_async;
+use yew_router::prelude::*;
 
-use crate::{api, utils};
-use crate::{
-    api::ApiError,
-    components::{
-        dialog::Dialog,
-        product::{
-            product_purchase_complete_dialog::ProductPurchaseCompleteDialog,
-            product_purchase_dialog::ProductPurchaseDialog,
-        },
-    },
+use crate::components::product::{
+    product_price_history_dialog::ProductPriceHistoryDialog,
+    product_purchase_flow::ProductPurchaseFlow,
 };
+use crate::{api, utils, Route};
 
 #[derive(Clone, Properties, PartialEq)]
 pub struct ProductItemProps {
     pub product: api::Product,
+    pub is_seller: bool,
     pub on_update: Callback<()>,
+    pub on_add_to_cart: Callback<api::Product>,
 }
 
 #[function_component(ProductItem)]
 pub fn product_item(props: &ProductItemProps) -> Html {
-    let flow_state = use_state(|| PurchaseFlow::None);
+    let purchasing = use_state(|| false);
 
     let buy_click_handler = {
-        let flow_state = flow_state.clone();
-        Callback::from(move |_| flow_state.set(PurchaseFlow::SelectingQuantity(false)))
+        let purchasing = purchasing.clone();
+        Callback::from(move |_| purchasing.set(true))
     };
 
-    let dialog_close_handler = {
-        let flow_state = flow_state.clone();
-        Callback::from(move |_| flow_state.set(PurchaseFlow::None))
+    let purchase_close_handler = {
+        let purchasing = purchasing.clone();
+        Callback::from(move |_| purchasing.set(false))
     };
 
-    let dialog_buy_handler = {
-        let flow_state = flow_state.clone();
+    let add_to_cart_handler = {
+        let on_add_to_cart = props.on_add_to_cart.clone();
+        let product = props.product.clone();
+        Callback::from(move |_| on_add_to_cart.emit(product.clone()))
+    };
+
+    let show_price_history = use_state(|| false);
+
+    let price_history_click_handler = {
+        let show_price_history = show_price_history.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default(); // avoid following the link
+            show_price_history.set(true);
+        })
+    };
+
+    let price_history_close_handler = {
+        let show_price_history = show_price_history.clone();
+        Callback::from(move |_| show_price_history.set(false))
+    };
+
+    let archive = {
         let on_update = props.on_update.clone();
         let product_id = props.product.id;
-        Callback::from(move |quantity: u32| {
-            let flow_state = flow_state.clone();
-            let on_update = on_update.clone();
-            flow_state.set(PurchaseFlow::SelectingQuantity(true));
-            spawn_local(async move {
-                let payload = api::PurchaseProductPayload { quantity };
-                match api::purchase_product(product_id, &payload).await {
-                    Ok(_) => {
-                        flow_state.set(PurchaseFlow::Complete);
-                        on_update.emit(());
-                    }
-                    Err(error) => flow_state.set(PurchaseFlow::Error(error)),
-                };
-            })
+        use_async(async move {
+            let res = api::archive_product(product_id).await;
+            if res.is_ok() {
+                on_update.emit(());
+            }
+            res
+        })
+    };
+
+    let handle_archive = {
+        let archive = archive.clone();
+        Callback::from(move |_| {
+            archive.run();
         })
     };
 
@@ -58,6 +74,16 @@ pub fn product_item(props: &ProductItemProps) -> Html {
 
     html! {
         <div class="product-item">
+            {
+                match (&product.image_url, &product.thumbnail_url) {
+                    (Some(image_url), Some(thumbnail_url)) => html! {
+                        <a href={image_url.clone()} target="_blank" class="product-image">
+                            <img src={thumbnail_url.clone()} alt={product.name.clone()} />
+                        </a>
+                    },
+                    _ => html! {},
+                }
+            }
             <div class="product-info">
                 <div class="product-info--name">{product.name.clone()}</div>
                 <div class="product-info--metadata">
@@ -65,7 +91,8 @@ pub fn product_item(props: &ProductItemProps) -> Html {
                     <span class="product-info--seller">{product.seller_name.clone()}</span>
                     {" | "}
                     <span class="product-info--stock">{product.stock}</span>
-                    {" in stock"}
+                    {" in stock | "}
+                    <a href="#" onclick={price_history_click_handler} class="product-info--history">{"Price history"}</a>
                 </div>
                 <div class="product-info--description">{product.description.as_ref().unwrap_or(&String::new())}</div>
             </div>
@@ -73,48 +100,52 @@ pub fn product_item(props: &ProductItemProps) -> Html {
                 {utils::format_display_price(product.price)}
             </div>
             <div class="product-actions">
+                {
+                    if props.is_seller {
+                        html! {
+                            <>
+                                <Link<Route> to={Route::ProductEditPage { id: product.id }} classes={classes!("btn", "product-actions--edit")}>{"Edit"}</Link<Route>>
+                                <Link<Route> to={Route::ProductRestockPage { id: product.id }} classes={classes!("btn", "product-actions--restock")}>{"Restock"}</Link<Route>>
+                                <button onclick={handle_archive} disabled={archive.loading} class="btn product-actions--archive">{"Archive"}</button>
+                            </>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                <button onclick={add_to_cart_handler} class="btn product-actions--add-to-cart">{"Add to cart"}</button>
                 <button onclick={buy_click_handler} class="btn product-actions--purchase">{"Buy"}</button>
+                {
+                    archive.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="product-actions--error">{error}</div>
+                    })
+                }
             </div>
             {
-                match &*flow_state {
-                    PurchaseFlow::SelectingQuantity(loading) => html! {
-                        <ProductPurchaseDialog
-                            loading={*loading}
+                if *purchasing {
+                    html! {
+                        <ProductPurchaseFlow
                             product={product.clone()}
-                            on_close={dialog_close_handler}
-                            on_buy={dialog_buy_handler}
+                            on_close={purchase_close_handler}
+                            on_update={&props.on_update}
                         />
-                    },
-                    PurchaseFlow::Error(error) => html! {
-                        <Dialog>
-                            <div class="card">
-                                <div class="card-header">
-                                    {"Error"}
-                                </div>
-                                <div class="card-error">
-                                    {error}
-                                </div>
-                                <div class="card-actions product-actions">
-                                    <button onclick={dialog_close_handler} class="btn product-actions--cancel">
-                                        {"Close"}
-                                    </button>
-                                </div>
-                            </div>
-                        </Dialog>
-                    },
-                    PurchaseFlow::Complete => html! {
-                        <ProductPurchaseCompleteDialog on_close={dialog_close_handler} />
-                    },
-                    PurchaseFlow::None => html! {},
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            {
+                if *show_price_history {
+                    html! {
+                        <ProductPriceHistoryDialog
+                            product={product.clone()}
+                            on_close={price_history_close_handler}
+                        />
+                    }
+                } else {
+                    html! {}
                 }
             }
         </div>
     }
 }
-
-pub enum PurchaseFlow {
-    None,
-    SelectingQuantity(bool),
-    Error(ApiError),
-    Complete,
-}
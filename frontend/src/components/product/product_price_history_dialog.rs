@@ -0,0 +1,83 @@
+use yew::prelude::*;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+
+use crate::{
+    api,
+    components::dialog::Dialog,
+    utils::{self, class_if, format_datetime},
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct ProductPriceHistoryDialogProps {
+    pub product: api::Product,
+    pub on_close: Callback<MouseEvent>,
+}
+
+#[function_component(ProductPriceHistoryDialog)]
+pub fn product_price_history_dialog(props: &ProductPriceHistoryDialogProps) -> Html {
+    let history = {
+        let product_id = props.product.id;
+        use_async_with_options(
+            async move { api::get_price_history(product_id).await },
+            UseAsyncOptions::enable_auto(),
+        )
+    };
+
+    html! {
+        <Dialog>
+            <div class={classes!("card", "products-card", class_if(history.loading, "card-loading"))}>
+                <div class="loading-bar" />
+                {
+                    history.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="card-error">{error}</div>
+                    })
+                }
+                <div class="card-header">
+                    {format!("Price history of {}", props.product.name.clone())}
+                </div>
+                <div class="card-content">
+                    <div class="price-history-list">
+                        {
+                            history.data.as_ref().map_or_else(|| html!{}, |entries| {
+                                if entries.is_empty() {
+                                    html! {
+                                        <p>{"The price of this product has never changed"}</p>
+                                    }
+                                } else {
+                                    entries.iter()
+                                        .map(|entry| {
+                                            html! {
+                                                <div class="price-history-item">
+                                                    <div class="price-history-item--prices">
+                                                        {format!(
+                                                            "{} → {}",
+                                                            utils::format_display_price(entry.old_price),
+                                                            utils::format_display_price(entry.new_price),
+                                                        )}
+                                                    </div>
+                                                    <div class="price-history-item--metadata">
+                                                        {format!("By {} at {}", entry.actor_name.clone(), format_datetime(entry.date))}
+                                                    </div>
+                                                    {
+                                                        entry.reason.as_ref().map_or_else(|| html!{}, |reason| html! {
+                                                            <div class="price-history-item--reason">{reason}</div>
+                                                        })
+                                                    }
+                                                </div>
+                                            }
+                                        })
+                                        .collect()
+                                }
+                            })
+                        }
+                    </div>
+                </div>
+                <div class="card-actions product-actions">
+                    <button onclick={&props.on_close} class="btn product-actions--done">
+                        {"Close"}
+                    </button>
+                </div>
+            </div>
+        </Dialog>
+    }
+}
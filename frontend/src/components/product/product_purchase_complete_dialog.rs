@@ -1,22 +1,28 @@
 use yew::prelude::*;
 
-use crate::components::dialog::Dialog;
+use crate::{components::dialog::Dialog, utils::class_if};
 
 #[derive(Clone, Properties, PartialEq)]
 pub struct ProductPurchaseCompleteDialogProps {
+    pub undoing: bool,
     pub on_close: Callback<MouseEvent>,
+    pub on_undo: Callback<MouseEvent>,
 }
 
 #[function_component(ProductPurchaseCompleteDialog)]
 pub fn product_purchase_complete_dialog(props: &ProductPurchaseCompleteDialogProps) -> Html {
     html! {
         <Dialog>
-            <div class="card">
+            <div class={classes!("card", class_if(props.undoing, "card-loading"))}>
+                <div class="loading-bar" />
                 <div class="card-header">
                     {"Purchase complete!"}
                 </div>
                 <div class="card-actions product-actions">
-                    <button onclick={&props.on_close} class="btn product-actions--done">
+                    <button onclick={&props.on_undo} disabled={props.undoing} class="btn product-actions--cancel">
+                        {"Undo"}
+                    </button>
+                    <button onclick={&props.on_close} disabled={props.undoing} class="btn product-actions--done">
                         {"Done"}
                     </button>
                 </div>
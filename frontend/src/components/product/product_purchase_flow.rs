@@ -0,0 +1,126 @@
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::api;
+use crate::{
+    api::ApiError,
+    components::{
+        dialog::Dialog,
+        product::{
+            product_purchase_complete_dialog::ProductPurchaseCompleteDialog,
+            product_purchase_dialog::ProductPurchaseDialog,
+        },
+    },
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct ProductPurchaseFlowProps {
+    pub product: api::Product,
+    pub on_close: Callback<()>,
+    pub on_update: Callback<()>,
+}
+
+/// Dialogs to buy a product, from picking the quantity until the purchase is complete
+#[function_component(ProductPurchaseFlow)]
+pub fn product_purchase_flow(props: &ProductPurchaseFlowProps) -> Html {
+    let flow_state = use_state(|| PurchaseFlow::SelectingQuantity(false));
+
+    let dialog_close_handler = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+
+    let dialog_buy_handler = {
+        let flow_state = flow_state.clone();
+        let on_update = props.on_update.clone();
+        let product_id = props.product.id;
+        Callback::from(move |quantity: u32| {
+            let flow_state = flow_state.clone();
+            let on_update = on_update.clone();
+            flow_state.set(PurchaseFlow::SelectingQuantity(true));
+            spawn_local(async move {
+                let payload = api::PurchaseProductPayload { quantity };
+                match api::purchase_product(product_id, &payload).await {
+                    Ok(purchase) => {
+                        flow_state.set(PurchaseFlow::Complete(purchase.id, false));
+                        on_update.emit(());
+                    }
+                    Err(error) => {
+                        // someone else bought it first, show the stock that is left
+                        if let ApiError::NotEnoughStock(_) = error {
+                            on_update.emit(());
+                        }
+                        flow_state.set(PurchaseFlow::Error(error));
+                    }
+                };
+            })
+        })
+    };
+
+    let dialog_undo_handler = {
+        let flow_state = flow_state.clone();
+        let on_close = props.on_close.clone();
+        let on_update = props.on_update.clone();
+        Callback::from(move |_| {
+            let flow_state = flow_state.clone();
+            let on_close = on_close.clone();
+            let on_update = on_update.clone();
+            let purchase_id = match *flow_state {
+                PurchaseFlow::Complete(purchase_id, _) => purchase_id,
+                _ => return,
+            };
+            flow_state.set(PurchaseFlow::Complete(purchase_id, true));
+            spawn_local(async move {
+                match api::cancel_purchase(purchase_id).await {
+                    Ok(_) => {
+                        on_update.emit(());
+                        on_close.emit(());
+                    }
+                    Err(error) => flow_state.set(PurchaseFlow::Error(error)),
+                };
+            })
+        })
+    };
+
+    match &*flow_state {
+        PurchaseFlow::SelectingQuantity(loading) => html! {
+            <ProductPurchaseDialog
+                loading={*loading}
+                product={props.product.clone()}
+                on_close={dialog_close_handler}
+                on_buy={dialog_buy_handler}
+            />
+        },
+        PurchaseFlow::Error(error) => html! {
+            <Dialog>
+                <div class="card">
+                    <div class="card-header">
+                        {"Error"}
+                    </div>
+                    <div class="card-error">
+                        {error}
+                    </div>
+                    <div class="card-actions product-actions">
+                        <button onclick={dialog_close_handler} class="btn product-actions--cancel">
+                            {"Close"}
+                        </button>
+                    </div>
+                </div>
+            </Dialog>
+        },
+        PurchaseFlow::Complete(_, undoing) => html! {
+            <ProductPurchaseCompleteDialog
+                undoing={*undoing}
+                on_close={dialog_close_handler}
+                on_undo={dialog_undo_handler}
+            />
+        },
+    }
+}
+
+pub enum PurchaseFlow {
+    SelectingQuantity(bool),
+    Error(ApiError),
+    /// Purchase id and whether it is being undone
+    Complete(u32, bool),
+}
@@ -1,3 +1,4 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew_hooks::use_async;
 
@@ -7,24 +8,72 @@ use crate::{api, components::purchase::purchase_item::PurchaseItem, utils};
 pub struct BuyerGroupedPurchasesProps {
     pub grouped_purchases: api::BuyerGroupedPurchases,
     pub on_update: Callback<()>,
+    pub on_settle: Callback<(String, api::Settlement)>,
 }
 
 #[function_component(BuyerGroupedPurchases)]
 pub fn buyer_grouped_purchases(props: &BuyerGroupedPurchasesProps) -> Html {
     let grouped_purchases = &props.grouped_purchases;
+    let payment_method_ref = use_node_ref();
     let settle = {
         let on_update = props.on_update.clone();
+        let on_settle = props.on_settle.clone();
         let buyer_id = props.grouped_purchases.buyer.id;
+        let buyer_name = props.grouped_purchases.buyer.name.clone();
         let purchase_count = props.grouped_purchases.purchases.len() as u32;
+        let payment_method_ref = payment_method_ref.clone();
         use_async(async move {
-            let res = api::pay_purchase_user_bulk(buyer_id, purchase_count).await;
-            if res.is_ok() {
+            let payment_method = api::PaymentMethod::from_key(
+                &payment_method_ref
+                    .cast::<HtmlSelectElement>()
+                    .unwrap()
+                    .value(),
+            );
+            let res = api::pay_purchase_user_bulk(buyer_id, purchase_count, payment_method).await;
+            if let Ok(settlement) = &res {
+                on_settle.emit((buyer_name, settlement.clone()));
                 on_update.emit(());
             }
             res
         })
     };
 
+    let payment_amount_ref = use_node_ref();
+    let record_payment = {
+        let on_update = props.on_update.clone();
+        let on_settle = props.on_settle.clone();
+        let buyer_id = props.grouped_purchases.buyer.id;
+        let buyer_name = props.grouped_purchases.buyer.name.clone();
+        let payment_method_ref = payment_method_ref.clone();
+        let payment_amount_ref = payment_amount_ref.clone();
+        use_async(async move {
+            let payment_method = api::PaymentMethod::from_key(
+                &payment_method_ref
+                    .cast::<HtmlSelectElement>()
+                    .unwrap()
+                    .value(),
+            );
+            let amount_input = payment_amount_ref.cast::<HtmlInputElement>().unwrap();
+            let amount = amount_input.value().parse().unwrap_or(0);
+            let res = api::record_payment(buyer_id, amount, payment_method).await;
+            if let Ok(settlement) = &res {
+                amount_input.set_value("");
+                if let Some(settlement) = settlement {
+                    on_settle.emit((buyer_name, settlement.clone()));
+                }
+                on_update.emit(());
+            }
+            res
+        })
+    };
+
+    let handle_record_payment = {
+        let record_payment = record_payment.clone();
+        Callback::from(move |_| {
+            record_payment.run();
+        })
+    };
+
     let handle_mark_as_paid = {
         let settle = settle.clone();
         Callback::from(move |_| {
@@ -43,11 +92,39 @@ pub fn buyer_grouped_purchases(props: &BuyerGroupedPurchasesProps) -> Html {
                 </div>
                 <div class="buyer-info--amount-due">
                     {utils::format_display_price(grouped_purchases.amount_due)}
+                    {
+                        if grouped_purchases.credit > 0 {
+                            html! {
+                                <span class="buyer-info--credit">
+                                    {format!("{} credit", utils::format_display_balance(grouped_purchases.credit))}
+                                </span>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
                 </div>
                 <div class="buyer-info--actions">
-                    <button onclick={handle_mark_as_paid} disabled={settle.loading} class="btn buyer-info--actions__pay">{"Settle"}</button>
+                    <select ref={payment_method_ref} class="buyer-info--actions__method">
+                        {
+                            api::PaymentMethod::ALL.iter()
+                                .map(|method| html! {
+                                    <option value={method.key()}>{method.to_string()}</option>
+                                })
+                                .collect::<Html>()
+                        }
+                    </select>
+                    <input ref={payment_amount_ref} type="number" min={1} placeholder="Cents" class="buyer-info--actions__amount" />
+                    <button onclick={handle_record_payment} disabled={record_payment.loading} class="btn buyer-info--actions__payment">{"Record payment"}</button>
+                    <button
+                        onclick={handle_mark_as_paid}
+                        disabled={settle.loading || grouped_purchases.purchases.is_empty()}
+                        class="btn buyer-info--actions__pay"
+                    >
+                        {"Settle"}
+                    </button>
                     {
-                        settle.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        settle.error.as_ref().or(record_payment.error.as_ref()).map_or_else(|| html!{}, |error| html! {
                             <div class="buyer-info--actions__error">{error}</div>
                         })
                     }
@@ -63,6 +140,7 @@ pub fn buyer_grouped_purchases(props: &BuyerGroupedPurchasesProps) -> Html {
                                     is_seller={true}
                                     purchase={purchase.clone()}
                                     on_update={&props.on_update}
+                                    on_settle={&props.on_settle}
                                 />
                             }
                         })
@@ -0,0 +1,97 @@
+use yew::prelude::*;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+
+use crate::{
+    api,
+    components::purchase::purchase_item::PurchaseItem,
+    utils::{self, class_if},
+};
+
+#[function_component(BuyerSummary)]
+pub fn buyer_summary() -> Html {
+    let sellers = use_async_with_options(
+        async move { api::buyer_summary().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    let refresh_purchases = {
+        let sellers = sellers.clone();
+        Callback::<()>::from(move |_| {
+            sellers.run();
+        })
+    };
+
+    html! {
+        <div class={classes!("card", "purchases-card", class_if(sellers.loading, "card-loading"))}>
+            <div class="loading-bar" />
+            {
+                sellers.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                    <div class="card-error">{error}</div>
+                })
+            }
+            <div class="card-header">
+                {"Amount Owed"}
+            </div>
+            <div class="card-content">
+                <div class="purchases-list">
+                    {
+                        sellers.data.as_ref().map_or_else(|| html!{}, |sellers| {
+                            if sellers.is_empty() {
+                                html! {
+                                    <p>{"You don't owe anything"}</p>
+                                }
+                            } else {
+                                sellers.iter()
+                                    .map(|grouped_purchases| {
+                                        html! {
+                                            <div class="grouped-buyer-item" key={grouped_purchases.seller.id}>
+                                                <div class="buyer-info">
+                                                    <div class="buyer-info--name">
+                                                        {grouped_purchases.seller.name.clone()}
+                                                        <span class="buyer-info--count-badge">
+                                                            {grouped_purchases.purchases.len()}
+                                                        </span>
+                                                    </div>
+                                                    <div class="buyer-info--amount-due">
+                                                        {utils::format_display_price(grouped_purchases.amount_due)}
+                                                        {
+                                                            if grouped_purchases.credit > 0 {
+                                                                html! {
+                                                                    <span class="buyer-info--credit">
+                                                                        {format!("{} credit", utils::format_display_balance(grouped_purchases.credit))}
+                                                                    </span>
+                                                                }
+                                                            } else {
+                                                                html! {}
+                                                            }
+                                                        }
+                                                    </div>
+                                                </div>
+                                                <div class="buyer-purchases purchases-list">
+                                                    {
+                                                        grouped_purchases.purchases.iter()
+                                                            .map(|purchase| {
+                                                                html! {
+                                                                    <PurchaseItem
+                                                                        key={purchase.id}
+                                                                        is_seller={false}
+                                                                        purchase={purchase.clone()}
+                                                                        on_update={&refresh_purchases}
+                                                                    />
+                                                                }
+                                                            })
+                                                            .collect::<Html>()
+                                                    }
+                                                </div>
+                                            </div>
+                                        }
+                                    })
+                                    .collect()
+                            }
+                        })
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -1,4 +1,8 @@
 pub mod buyer_grouped_purchases;
+pub mod buyer_summary;
 pub mod purchase_item;
+pub mod purchase_refund_dialog;
 pub mod purchases_list;
+pub mod receipt_item;
 pub mod seller_summary;
+pub mod wallets_list;
@@ -3,6 +3,7 @@ use yew_hooks::use_async;
 
 use crate::{
     api,
+    components::purchase::purchase_refund_dialog::PurchaseRefundDialog,
     utils::{self, class_if, format_datetime},
 };
 
@@ -11,6 +12,8 @@ pub struct PurchaseItemProps {
     pub purchase: api::Purchase,
     pub is_seller: bool,
     pub on_update: Callback<()>,
+    #[prop_or_default]
+    pub on_settle: Callback<(String, api::Settlement)>,
 }
 
 #[function_component(PurchaseItem)]
@@ -18,10 +21,13 @@ pub fn purchase_item(props: &PurchaseItemProps) -> Html {
     let purchase = &props.purchase;
     let settle = {
         let on_update = props.on_update.clone();
+        let on_settle = props.on_settle.clone();
         let purchase_id = purchase.id;
+        let buyer_name = purchase.buyer.name.clone();
         use_async(async move {
             let res = api::pay_purchase(purchase_id).await;
-            if res.is_ok() {
+            if let Ok(settlement) = &res {
+                on_settle.emit((buyer_name, settlement.clone()));
                 on_update.emit(());
             }
             res
@@ -35,6 +41,27 @@ pub fn purchase_item(props: &PurchaseItemProps) -> Html {
         })
     };
 
+    let refunding = use_state(|| false);
+
+    let handle_refund_click = {
+        let refunding = refunding.clone();
+        Callback::from(move |_| refunding.set(true))
+    };
+
+    let handle_refund_close = {
+        let refunding = refunding.clone();
+        Callback::from(move |_| refunding.set(false))
+    };
+
+    let handle_refund_complete = {
+        let refunding = refunding.clone();
+        let on_update = props.on_update.clone();
+        Callback::from(move |_| {
+            refunding.set(false);
+            on_update.emit(());
+        })
+    };
+
     html! {
         <div class="purchase-item">
             <div class="purchase-info">
@@ -75,6 +102,22 @@ pub fn purchase_item(props: &PurchaseItemProps) -> Html {
                         None => html! {}
                     }
                 }
+                {
+                    purchase.refunds.iter()
+                        .map(|refund| {
+                            html! {
+                                <div class="purchase-info--refund">
+                                    {format!(
+                                        "Refunded {} at {}: {}",
+                                        utils::format_display_price(refund.amount),
+                                        format_datetime(refund.date),
+                                        refund.reason.clone(),
+                                    )}
+                                </div>
+                            }
+                        })
+                        .collect::<Html>()
+                }
                 <div class="purchase-info--description">{purchase.product.description.as_ref().unwrap_or(&String::new())}</div>
             </div>
             <div class="purchase-price">
@@ -84,6 +127,7 @@ pub fn purchase_item(props: &PurchaseItemProps) -> Html {
                 if props.is_seller {
                     html! {
                         <div class="purchase-actions">
+                            <button onclick={handle_refund_click} class="btn purchase-actions--refund">{"Refund"}</button>
                             <button onclick={handle_settle} disabled={settle.loading} class="btn purchase-actions--pay">{"Settle"}</button>
                             {
                                 settle.error.as_ref().map_or_else(|| html!{}, |error| html! {
@@ -96,6 +140,19 @@ pub fn purchase_item(props: &PurchaseItemProps) -> Html {
                     html! {}
                 }
             }
+            {
+                if *refunding {
+                    html! {
+                        <PurchaseRefundDialog
+                            purchase={purchase.clone()}
+                            on_close={handle_refund_close}
+                            on_refund={handle_refund_complete}
+                        />
+                    }
+                } else {
+                    html! {}
+                }
+            }
         </div>
     }
 }
@@ -0,0 +1,112 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{api, components::dialog::Dialog, utils::class_if};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct PurchaseRefundDialogProps {
+    pub purchase: api::Purchase,
+    pub on_close: Callback<()>,
+    pub on_refund: Callback<()>,
+}
+
+#[function_component(PurchaseRefundDialog)]
+pub fn purchase_refund_dialog(props: &PurchaseRefundDialogProps) -> Html {
+    let amount_ref = use_node_ref();
+    let reason_ref = use_node_ref();
+    let returned_quantity_ref = use_node_ref();
+
+    let state = {
+        let purchase_id = props.purchase.id;
+        let on_refund = props.on_refund.clone();
+        let amount_ref = amount_ref.clone();
+        let reason_ref = reason_ref.clone();
+        let returned_quantity_ref = returned_quantity_ref.clone();
+
+        use_async(async move {
+            let returned_quantity = returned_quantity_ref
+                .cast::<HtmlInputElement>()
+                .unwrap()
+                .value_as_number() as u32;
+            let payload = api::RefundPurchasePayload {
+                amount: amount_ref
+                    .cast::<HtmlInputElement>()
+                    .unwrap()
+                    .value_as_number() as u32,
+                reason: reason_ref.cast::<HtmlInputElement>().unwrap().value(),
+                returned_quantity: Some(returned_quantity),
+            };
+
+            let res = api::refund_purchase(purchase_id, &payload).await;
+            if res.is_ok() {
+                on_refund.emit(());
+            }
+            res
+        })
+    };
+
+    let handle_submit = {
+        let state = state.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // prevent form submission
+            state.run();
+        })
+    };
+
+    let handle_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+
+    let purchase = &props.purchase;
+
+    html! {
+        <Dialog>
+            <div class={classes!("card", class_if(state.loading, "card-loading"))}>
+                <div class="loading-bar" />
+                {
+                    state.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="card-error">{error}</div>
+                    })
+                }
+                <div class="card-header">
+                    {format!("Refund {}", purchase.product.name.clone())}
+                </div>
+                <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                    <label for="refund--amount">{"Amount (cents) (*)"}</label>
+                    <input
+                        ref={amount_ref}
+                        type="number"
+                        min={1}
+                        id="refund--amount"
+                        required={true}
+                        value={(purchase.unit_price * purchase.quantity).to_string()}
+                    />
+
+                    <label for="refund--reason">{"Reason (*)"}</label>
+                    <input ref={reason_ref} type="text" id="refund--reason" required={true} />
+
+                    <label for="refund--returned-quantity">{"Items returned to stock"}</label>
+                    <input
+                        ref={returned_quantity_ref}
+                        type="number"
+                        min={0}
+                        max={purchase.quantity.to_string()}
+                        id="refund--returned-quantity"
+                        value="0"
+                    />
+
+                    <div class="card-actions product-actions">
+                        <button type="button" onclick={handle_close} disabled={state.loading} class="btn product-actions--cancel">
+                            {"Cancel"}
+                        </button>
+                        <button type="submit" disabled={state.loading} class="btn purchase-actions--refund">
+                            {"Refund"}
+                        </button>
+                    </div>
+                </form>
+            </div>
+        </Dialog>
+    }
+}
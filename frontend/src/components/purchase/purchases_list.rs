@@ -1,27 +1,120 @@
+use chrono::Duration;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
-use yew_hooks::{use_async_with_options, UseAsyncOptions};
 
-use crate::{api, components::purchase::purchase_item::PurchaseItem, utils::class_if};
+use crate::{
+    api::{self, ApiError},
+    components::purchase::purchase_item::PurchaseItem,
+    utils::{class_if, parse_date_input},
+};
 
 #[function_component(PurchasesList)]
 pub fn purchases_list() -> Html {
-    let purchases = use_async_with_options(
-        async move { api::list_purchases().await },
-        UseAsyncOptions::enable_auto(),
-    );
+    let filter = use_state(api::PurchaseFilter::default);
+    let purchases = use_state(Vec::<api::Purchase>::new);
+    let next_cursor = use_state(|| None::<api::PurchaseCursor>);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<ApiError>);
 
-    let refresh_purchases = {
+    // Loads the first page when no cursor is given, otherwise appends the next one
+    let load_page = {
+        let filter = filter.clone();
         let purchases = purchases.clone();
+        let next_cursor = next_cursor.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |cursor: Option<api::PurchaseCursor>| {
+            let filter = (*filter).clone();
+            let mut purchases_list = if cursor.is_some() {
+                (*purchases).clone()
+            } else {
+                Vec::new()
+            };
+            let purchases = purchases.clone();
+            let next_cursor = next_cursor.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            loading.set(true);
+            spawn_local(async move {
+                match api::list_purchases(&filter, cursor.as_ref()).await {
+                    Ok(page) => {
+                        purchases_list.extend(page.purchases);
+                        purchases.set(purchases_list);
+                        next_cursor.set(page.next_cursor);
+                        error.set(None);
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    {
+        let load_page = load_page.clone();
+        use_effect_with_deps(
+            move |_| {
+                load_page.emit(None);
+                || {}
+            },
+            (*filter).clone(),
+        );
+    }
+
+    let refresh_purchases = {
+        let load_page = load_page.clone();
         Callback::<()>::from(move |_| {
-            purchases.run();
+            load_page.emit(None);
+        })
+    };
+
+    let handle_load_more = {
+        let next_cursor = next_cursor.clone();
+        Callback::from(move |_| {
+            if let Some(cursor) = &*next_cursor {
+                load_page.emit(Some(cursor.clone()));
+            }
+        })
+    };
+
+    let from_ref = use_node_ref();
+    let to_ref = use_node_ref();
+    let paid_ref = use_node_ref();
+    let handle_filter_change = {
+        let filter = filter.clone();
+        let from_ref = from_ref.clone();
+        let to_ref = to_ref.clone();
+        let paid_ref = paid_ref.clone();
+        Callback::from(move |_: Event| {
+            let from = parse_date_input(&from_ref.cast::<HtmlInputElement>().unwrap().value());
+            // the selected day is included
+            let to = parse_date_input(&to_ref.cast::<HtmlInputElement>().unwrap().value())
+                .map(|to| to + Duration::days(1));
+            let paid = match paid_ref
+                .cast::<HtmlSelectElement>()
+                .unwrap()
+                .value()
+                .as_str()
+            {
+                "paid" => Some(true),
+                "unpaid" => Some(false),
+                _ => None,
+            };
+            filter.set(api::PurchaseFilter {
+                from,
+                to,
+                paid,
+                ..(*filter).clone()
+            });
         })
     };
 
     html! {
-        <div class={classes!("card", "purchases-card", class_if(purchases.loading, "card-loading"))}>
+        <div class={classes!("card", "purchases-card", class_if(*loading, "card-loading"))}>
             <div class="loading-bar" />
             {
-                purchases.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                (*error).as_ref().map_or_else(|| html!{}, |error| html! {
                     <div class="card-error">{error}</div>
                 })
             }
@@ -29,30 +122,48 @@ pub fn purchases_list() -> Html {
                 {"Purchases"}
             </div>
             <div class="card-content">
+                <div class="purchases-filter">
+                    <label for="purchases-filter--from">{"From"}</label>
+                    <input ref={from_ref} onchange={handle_filter_change.clone()} type="date" id="purchases-filter--from" />
+                    <label for="purchases-filter--to">{"To"}</label>
+                    <input ref={to_ref} onchange={handle_filter_change.clone()} type="date" id="purchases-filter--to" />
+                    <select ref={paid_ref} onchange={handle_filter_change}>
+                        <option value="all">{"All"}</option>
+                        <option value="unpaid">{"Unpaid"}</option>
+                        <option value="paid">{"Paid"}</option>
+                    </select>
+                </div>
                 <div class="purchases-list">
                     {
-                        purchases.data.as_ref().map_or_else(|| html!{}, |purchases_list| {
-                            if purchases_list.is_empty() {
-                                html! {
-                                    <p>{"You haven't bought any products yet"}</p>
-                                }
-                            } else {
-                                purchases_list.iter()
-                                    .map(|purchase| {
-                                        html! {
-                                            <PurchaseItem
-                                                key={purchase.id}
-                                                is_seller={false}
-                                                purchase={purchase.clone()}
-                                                on_update={&refresh_purchases}
-                                            />
-                                        }
-                                    })
-                                    .collect()
+                        if purchases.is_empty() && !*loading {
+                            html! {
+                                <p>{"No purchases found"}</p>
                             }
-                        })
+                        } else {
+                            purchases.iter()
+                                .map(|purchase| {
+                                    html! {
+                                        <PurchaseItem
+                                            key={purchase.id}
+                                            is_seller={false}
+                                            purchase={purchase.clone()}
+                                            on_update={&refresh_purchases}
+                                        />
+                                    }
+                                })
+                                .collect()
+                        }
                     }
                 </div>
+                {
+                    if next_cursor.is_some() {
+                        html! {
+                            <button onclick={handle_load_more} disabled={*loading} class="btn purchases-load-more">{"Load more"}</button>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
             </div>
         </div>
     }
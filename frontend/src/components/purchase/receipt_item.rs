@@ -0,0 +1,78 @@
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{api, utils};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct ReceiptItemProps {
+    pub purchase: api::Purchase,
+    pub is_seller: bool,
+    pub on_update: Callback<()>,
+}
+
+#[function_component(ReceiptItem)]
+pub fn receipt_item(props: &ReceiptItemProps) -> Html {
+    let purchase = &props.purchase;
+    let unpay = {
+        let on_update = props.on_update.clone();
+        let purchase_id = purchase.id;
+        use_async(async move {
+            let res = api::unpay_purchase(purchase_id).await;
+            if res.is_ok() {
+                on_update.emit(());
+            }
+            res
+        })
+    };
+
+    let handle_unpay = {
+        let unpay = unpay.clone();
+        Callback::from(move |_| {
+            unpay.run();
+        })
+    };
+
+    let refunded: u32 = purchase.refunds.iter().map(|refund| refund.amount).sum();
+
+    html! {
+        <div class="purchase-item">
+            <div class="purchase-info">
+                <div class="purchase-info--name">{purchase.product.name.clone()}</div>
+                <div class="purchase-info--metadata">
+                    <span class="purchase-info--quantity">{purchase.quantity}</span>
+                    {format!(" x {}", utils::format_display_price(purchase.unit_price))}
+                </div>
+                {
+                    if refunded > 0 {
+                        html! {
+                            <div class="purchase-info--refund">
+                                {format!("Refunded {}", utils::format_display_price(refunded))}
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+            <div class="purchase-price">
+                {utils::format_display_price(purchase.unit_price * purchase.quantity - refunded)}
+            </div>
+            {
+                if props.is_seller {
+                    html! {
+                        <div class="purchase-actions">
+                            <button onclick={handle_unpay} disabled={unpay.loading} class="btn purchase-actions--unpay">{"Unpay"}</button>
+                            {
+                                unpay.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                                    <div class="purchase-actions--error">{error}</div>
+                                })
+                            }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
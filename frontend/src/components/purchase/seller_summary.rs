@@ -1,8 +1,11 @@
+use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 use yew_hooks::{use_async_with_options, UseAsyncOptions};
 
 use crate::{
-    api, components::purchase::buyer_grouped_purchases::BuyerGroupedPurchases, utils::class_if,
+    api::{self, ApiError},
+    components::purchase::buyer_grouped_purchases::BuyerGroupedPurchases,
+    utils::class_if,
 };
 
 #[function_component(SellerSummary)]
@@ -19,6 +22,38 @@ pub fn seller_summary() -> Html {
         })
     };
 
+    let last_settlement = use_state(|| SettlementFlow::None);
+
+    let handle_settle = {
+        let last_settlement = last_settlement.clone();
+        Callback::from(move |(buyer_name, settlement): (String, api::Settlement)| {
+            last_settlement.set(SettlementFlow::Settled(buyer_name, settlement.id))
+        })
+    };
+
+    let handle_undo_settlement = {
+        let last_settlement = last_settlement.clone();
+        let refresh_purchases = refresh_purchases.clone();
+        Callback::from(move |_| {
+            let last_settlement = last_settlement.clone();
+            let refresh_purchases = refresh_purchases.clone();
+            let settlement_id = match &*last_settlement {
+                SettlementFlow::Settled(_, settlement_id) => *settlement_id,
+                _ => return,
+            };
+            last_settlement.set(SettlementFlow::Reverting);
+            spawn_local(async move {
+                match api::revert_settlement(settlement_id).await {
+                    Ok(_) => {
+                        last_settlement.set(SettlementFlow::None);
+                        refresh_purchases.emit(());
+                    }
+                    Err(error) => last_settlement.set(SettlementFlow::Error(error)),
+                };
+            })
+        })
+    };
+
     html! {
         <div class={classes!("card", "purchases-card", class_if(buyers.loading, "card-loading"))}>
             <div class="loading-bar" />
@@ -30,6 +65,25 @@ pub fn seller_summary() -> Html {
             <div class="card-header">
                 {"Products Sold"}
             </div>
+            {
+                match &*last_settlement {
+                    SettlementFlow::Settled(buyer_name, _) => html! {
+                        <div class="settlement-notice">
+                            <span>{format!("Settled purchases of {}.", buyer_name)}</span>
+                            <button onclick={handle_undo_settlement} class="btn settlement-notice--undo">{"Undo"}</button>
+                        </div>
+                    },
+                    SettlementFlow::Reverting => html! {
+                        <div class="settlement-notice">
+                            <span>{"Reverting settlement..."}</span>
+                        </div>
+                    },
+                    SettlementFlow::Error(error) => html! {
+                        <div class="card-error">{format!("Failed to revert settlement: {}", error)}</div>
+                    },
+                    SettlementFlow::None => html! {},
+                }
+            }
             <div class="card-content">
                 <div class="purchases-list">
                     {
@@ -46,6 +100,7 @@ pub fn seller_summary() -> Html {
                                                 key={buyer.buyer.id}
                                                 grouped_purchases={buyer.clone()}
                                                 on_update={&refresh_purchases}
+                                                on_settle={&handle_settle}
                                             />
                                         }
                                     })
@@ -58,3 +113,11 @@ pub fn seller_summary() -> Html {
         </div>
     }
 }
+
+pub enum SettlementFlow {
+    None,
+    /// Buyer name and settlement id of the last settle action
+    Settled(String, u32),
+    Reverting,
+    Error(ApiError),
+}
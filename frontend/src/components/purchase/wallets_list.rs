@@ -0,0 +1,59 @@
+use yew::prelude::*;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+
+use crate::{
+    api,
+    utils::{self, class_if},
+};
+
+#[function_component(WalletsList)]
+pub fn wallets_list() -> Html {
+    let wallets = use_async_with_options(
+        async move { api::list_wallets().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    html! {
+        <div class={classes!("card", "purchases-card", class_if(wallets.loading, "card-loading"))}>
+            <div class="loading-bar" />
+            {
+                wallets.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                    <div class="card-error">{error}</div>
+                })
+            }
+            <div class="card-header">
+                {"Credit"}
+            </div>
+            <div class="card-content">
+                <div class="purchases-list">
+                    {
+                        wallets.data.as_ref().map_or_else(|| html!{}, |wallets| {
+                            if wallets.is_empty() {
+                                html! {
+                                    <p>{"You don't have credit with any seller. Ask a seller to top up your wallet."}</p>
+                                }
+                            } else {
+                                wallets.iter()
+                                    .map(|wallet| {
+                                        html! {
+                                            <div class="settlement-item" key={wallet.seller.id}>
+                                                <div class="settlement-info">
+                                                    <div class="settlement-info--name">
+                                                        {wallet.seller.name.clone()}
+                                                    </div>
+                                                </div>
+                                                <div class="settlement-amount">
+                                                    {utils::format_display_balance(wallet.balance)}
+                                                </div>
+                                            </div>
+                                        }
+                                    })
+                                    .collect()
+                            }
+                        })
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,146 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{
+    api,
+    utils::{class_if, format_datetime},
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct AdminUserItemProps {
+    pub user: api::User,
+    pub on_update: Callback<()>,
+}
+
+/// A user in the admin panel, with controls to edit their account
+#[function_component(AdminUserItem)]
+pub fn admin_user_item(props: &AdminUserItemProps) -> Html {
+    let name_ref = use_node_ref();
+    let role_ref = use_node_ref();
+    let disabled_ref = use_node_ref();
+    let save = {
+        let user_id = props.user.id;
+        let on_update = props.on_update.clone();
+        let name_ref = name_ref.clone();
+        let role_ref = role_ref.clone();
+        let disabled_ref = disabled_ref.clone();
+        use_async(async move {
+            let payload = api::AdminUpdateUserPayload {
+                name: name_ref.cast::<HtmlInputElement>().unwrap().value(),
+                role: api::UserRole::from_key(
+                    &role_ref.cast::<HtmlSelectElement>().unwrap().value(),
+                ),
+                disabled: disabled_ref.cast::<HtmlInputElement>().unwrap().checked(),
+            };
+            let res = api::admin_update_user(user_id, &payload).await;
+            if res.is_ok() {
+                on_update.emit(());
+            }
+            res
+        })
+    };
+
+    let password_ref = use_node_ref();
+    let reset_password = {
+        let user_id = props.user.id;
+        let password_ref = password_ref.clone();
+        use_async(async move {
+            let password_input = password_ref.cast::<HtmlInputElement>().unwrap();
+            let payload = api::AdminResetPasswordPayload {
+                password: password_input.value(),
+            };
+            let res = api::admin_reset_password(user_id, &payload).await;
+            if res.is_ok() {
+                password_input.set_value("");
+            }
+            res
+        })
+    };
+
+    let create_reset_link = {
+        let user_id = props.user.id;
+        use_async(async move { api::admin_create_password_reset(user_id).await })
+    };
+
+    let handle_save = {
+        let save = save.clone();
+        Callback::from(move |_| {
+            save.run();
+        })
+    };
+
+    let handle_reset_password = {
+        let reset_password = reset_password.clone();
+        Callback::from(move |_| {
+            reset_password.run();
+        })
+    };
+
+    let handle_create_reset_link = {
+        let create_reset_link = create_reset_link.clone();
+        Callback::from(move |_| {
+            create_reset_link.run();
+        })
+    };
+
+    let user = &props.user;
+    let loading = save.loading || reset_password.loading || create_reset_link.loading;
+    let error = save
+        .error
+        .as_ref()
+        .or(reset_password.error.as_ref())
+        .or(create_reset_link.error.as_ref());
+
+    html! {
+        <div class={classes!("admin-user-item", class_if(user.disabled, "admin-user-item--disabled"))}>
+            <div class="admin-user-item--info">
+                <input ref={name_ref} type="text" value={user.name.clone()} maxlength="30" class="admin-user-item--name" />
+                <span class="admin-user-item--phone">{user.phone_number.clone()}</span>
+            </div>
+            <div class="admin-user-item--actions">
+                <select ref={role_ref}>
+                    {
+                        api::UserRole::ALL.iter()
+                            .map(|role| html! {
+                                <option value={role.key()} selected={*role == user.role}>{role.to_string()}</option>
+                            })
+                            .collect::<Html>()
+                    }
+                </select>
+                <label>
+                    <input ref={disabled_ref} type="checkbox" checked={user.disabled} />
+                    {"Disabled"}
+                </label>
+                <button onclick={handle_save} disabled={loading} class="btn">{"Save"}</button>
+                <input ref={password_ref} type="password" minlength="8" placeholder="New password" />
+                <button onclick={handle_reset_password} disabled={loading} class="btn">{"Reset password"}</button>
+                {
+                    if reset_password.data.is_some() {
+                        html! { <span class="admin-user-item--notice">{"Password changed"}</span> }
+                    } else {
+                        html! {}
+                    }
+                }
+                <button onclick={handle_create_reset_link} disabled={loading} class="btn">{"Reset link"}</button>
+                {
+                    create_reset_link.data.as_ref().map_or_else(|| html!{}, |reset| html! {
+                        <div class="admin-user-item--reset-link">
+                            {"Send this link to the user, it works once until "}
+                            {format_datetime(reset.expiry_date)}
+                            {": "}
+                            <a href={format!("/reset-password?token={}", reset.token)}>
+                                {format!("/reset-password?token={}", reset.token)}
+                            </a>
+                        </div>
+                    })
+                }
+                {
+                    error.map_or_else(|| html!{}, |error| html! {
+                        <div class="admin-user-item--error">{error}</div>
+                    })
+                }
+            </div>
+        </div>
+    }
+}
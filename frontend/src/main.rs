@@ -9,8 +9,12 @@ mod api;
 mod utils;
 
 use pages::{
-    login_page::LoginPage, product_insert_page::ProductInsertPage, product_page::ProductPage,
-    purchases_page::PurchasesPage,
+    admin_page::AdminPage, invites_page::InvitesPage, login_page::LoginPage,
+    product_edit_page::ProductEditPage, product_insert_page::ProductInsertPage,
+    product_page::ProductPage, product_restock_page::ProductRestockPage, profile_page::ProfilePage,
+    purchases_page::PurchasesPage, register_page::RegisterPage,
+    reset_password_page::ResetPasswordPage, settings_page::SettingsPage,
+    settlement_page::SettlementPage, settlements_page::SettlementsPage,
 };
 
 #[derive(Clone, Routable, PartialEq)]
@@ -20,21 +24,51 @@ enum Route {
     Home,
     #[at("/login")]
     Login,
+    #[at("/register")]
+    Register,
+    #[at("/reset-password")]
+    ResetPassword,
     #[at("/products")]
     ProductPage,
     #[at("/product/insert")]
     ProductInsertPage,
+    #[at("/product/:id/edit")]
+    ProductEditPage { id: u32 },
+    #[at("/product/:id/restock")]
+    ProductRestockPage { id: u32 },
     #[at("/purchases")]
     PurchasesPage,
+    #[at("/settlements")]
+    SettlementsPage,
+    #[at("/settlement/:id")]
+    SettlementPage { id: u32 },
+    #[at("/invites")]
+    InvitesPage,
+    #[at("/profile")]
+    ProfilePage,
+    #[at("/settings")]
+    SettingsPage,
+    #[at("/admin")]
+    AdminPage,
 }
 
 fn switch(routes: &Route) -> Html {
     match routes {
         Route::Home => html! { <Redirect<Route> to={Route::ProductPage} /> },
         Route::Login => html! { <LoginPage /> },
+        Route::Register => html! { <RegisterPage /> },
+        Route::ResetPassword => html! { <ResetPasswordPage /> },
         Route::ProductPage => html! { <ProductPage /> },
         Route::ProductInsertPage => html! { <ProductInsertPage /> },
+        Route::ProductEditPage { id } => html! { <ProductEditPage id={*id} /> },
+        Route::ProductRestockPage { id } => html! { <ProductRestockPage id={*id} /> },
         Route::PurchasesPage => html! { <PurchasesPage /> },
+        Route::SettlementsPage => html! { <SettlementsPage /> },
+        Route::SettlementPage { id } => html! { <SettlementPage id={*id} /> },
+        Route::InvitesPage => html! { <InvitesPage /> },
+        Route::ProfilePage => html! { <ProfilePage /> },
+        Route::SettingsPage => html! { <SettingsPage /> },
+        Route::AdminPage => html! { <AdminPage /> },
     }
 }
 
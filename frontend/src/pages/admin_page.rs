@@ -0,0 +1,137 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_async_with_options, UseAsyncOptions};
+
+use crate::{
+    api,
+    components::{footer::Footer, navbar::Navbar, user::admin_user_item::AdminUserItem},
+    hooks::auth::use_auth,
+    utils::{class_if, PHONE_PLACEHOLDER},
+};
+
+#[function_component(AdminPage)]
+pub fn admin_page() -> Html {
+    use_auth();
+    let users = use_async_with_options(
+        async move { api::admin_list_users().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    let name_ref = use_node_ref();
+    let phone_ref = use_node_ref();
+    let password_ref = use_node_ref();
+    let role_ref = use_node_ref();
+    let create = {
+        let users = users.clone();
+        let name_ref = name_ref.clone();
+        let phone_ref = phone_ref.clone();
+        let password_ref = password_ref.clone();
+        let role_ref = role_ref.clone();
+        use_async(async move {
+            let inputs = [&name_ref, &phone_ref, &password_ref]
+                .map(|input_ref| input_ref.cast::<HtmlInputElement>().unwrap());
+            let payload = api::AdminCreateUserPayload {
+                name: inputs[0].value(),
+                phone_number: inputs[1].value(),
+                password: inputs[2].value(),
+                role: api::UserRole::from_key(
+                    &role_ref.cast::<HtmlSelectElement>().unwrap().value(),
+                ),
+            };
+            let res = api::admin_create_user(&payload).await;
+            if res.is_ok() {
+                for input in inputs {
+                    input.set_value("");
+                }
+                users.run();
+            }
+            res
+        })
+    };
+
+    let handle_create = {
+        let create = create.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // avoid form submission
+            create.run();
+        })
+    };
+
+    let handle_update = {
+        let users = users.clone();
+        Callback::from(move |_| {
+            users.run();
+        })
+    };
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "purchases-card", class_if(users.loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        users.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Users"}
+                    </div>
+                    <div class="card-content">
+                        {
+                            users.data.as_ref().map_or_else(|| html!{}, |users| {
+                                users.iter()
+                                    .map(|user| html! {
+                                        <AdminUserItem
+                                            key={user.id}
+                                            user={user.clone()}
+                                            on_update={&handle_update}
+                                        />
+                                    })
+                                    .collect::<Html>()
+                            })
+                        }
+                    </div>
+                </div>
+                <div class={classes!("card", "login-card", class_if(create.loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        create.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Create user"}
+                    </div>
+                    <div class="card-content">
+                        <form class="form form-vertical form-margin-top" onsubmit={handle_create}>
+                            <label for="admin--name">{"Name"}</label>
+                            <input ref={name_ref} type="text" id="admin--name" required={true} maxlength="30" />
+
+                            <label for="admin--phone">{"Phone Number"}</label>
+                            <input ref={phone_ref} type="tel" id="admin--phone" required={true} placeholder={PHONE_PLACEHOLDER} />
+
+                            <label for="admin--password">{"Password"}</label>
+                            <input ref={password_ref} type="password" id="admin--password" required={true} minlength="8" />
+
+                            <label for="admin--role">{"Role"}</label>
+                            <select ref={role_ref} id="admin--role">
+                                {
+                                    api::UserRole::ALL.iter()
+                                        .map(|role| html! {
+                                            <option value={role.key()}>{role.to_string()}</option>
+                                        })
+                                        .collect::<Html>()
+                                }
+                            </select>
+
+                            <button type="submit" disabled={create.loading} class="btn btn--full-width btn--primary">{"Create"}</button>
+                        </form>
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::Local;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_async_with_options, UseAsyncOptions};
+
+use crate::{
+    api,
+    components::{footer::Footer, navbar::Navbar},
+    hooks::auth::use_auth,
+    utils::{class_if, format_datetime},
+};
+
+#[function_component(InvitesPage)]
+pub fn invites_page() -> Html {
+    use_auth();
+    let invites = use_async_with_options(
+        async move { api::list_invites().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    let create = {
+        let invites = invites.clone();
+        use_async(async move {
+            let res = api::create_invite().await;
+            if res.is_ok() {
+                invites.run();
+            }
+            res
+        })
+    };
+
+    let handle_create = {
+        let create = create.clone();
+        Callback::from(move |_| {
+            create.run();
+        })
+    };
+
+    let loading = invites.loading || create.loading;
+    let error = create.error.as_ref().or(invites.error.as_ref());
+    let now = Local::now();
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "purchases-card", class_if(loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        error.map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Invites"}
+                    </div>
+                    <div class="card-content">
+                        <p>{"Each invite code lets one person register an account."}</p>
+                        <button onclick={handle_create} disabled={loading} class="btn btn--full-width btn--primary">
+                            {"Create invite"}
+                        </button>
+                        <div class="purchases-list">
+                            {
+                                invites.data.as_ref().map_or_else(|| html!{}, |invites| {
+                                    invites.iter()
+                                        .map(|invite| {
+                                            let status = match &invite.used_by {
+                                                Some(user) => format!(
+                                                    "Used by {} on {}",
+                                                    user.name,
+                                                    invite.used_date.map(format_datetime).unwrap_or_default()
+                                                ),
+                                                None if invite.expiry_date <= now => "Expired".to_string(),
+                                                None => format!("Expires on {}", format_datetime(invite.expiry_date)),
+                                            };
+                                            let available = invite.used_by.is_none() && invite.expiry_date > now;
+                                            html! {
+                                                <div class="settlement-item" key={invite.code.clone()}>
+                                                    <div class="settlement-info">
+                                                        <div class="settlement-info--name invite-code">
+                                                            {invite.code.clone()}
+                                                        </div>
+                                                        <div class="settlement-info--metadata">
+                                                            {status}
+                                                        </div>
+                                                    </div>
+                                                    {
+                                                        if available {
+                                                            html! {
+                                                                <a href={format!("/register?code={}", invite.code)} class="btn">
+                                                                    {"Registration link"}
+                                                                </a>
+                                                            }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </div>
+                                            }
+                                        })
+                                        .collect::<Html>()
+                                })
+                            }
+                        </div>
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
@@ -5,7 +5,7 @@ use yew_router::prelude::*;
 
 use crate::{
     api::{self, ApiError},
-    utils::class_if,
+    utils::{class_if, PHONE_PLACEHOLDER},
     Route,
 };
 
@@ -59,13 +59,17 @@ pub fn login_page() -> Html {
                 <div class="card-content">
                     <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
                         <label for="login--phone">{"Phone Number"}</label>
-                        <input ref={phone_ref} type="text" id="login--phone" />
+                        <input ref={phone_ref} type="tel" id="login--phone" placeholder={PHONE_PLACEHOLDER} />
 
                         <label for="login--password">{"Password"}</label>
                         <input ref={password_ref} type="password" id="login--password" />
 
                         <button type="submit" disabled={state.loading} class="btn btn--full-width btn--primary">{"Login"}</button>
                     </form>
+                    <p class="login-alternative">
+                        {"Have an invite code? "}
+                        <Link<Route> to={Route::Register}>{"Register"}</Link<Route>>
+                    </p>
                 </div>
             </div>
         </main>
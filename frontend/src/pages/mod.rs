@@ -1,4 +1,14 @@
+pub mod admin_page;
+pub mod invites_page;
 pub mod login_page;
+pub mod product_edit_page;
 pub mod product_insert_page;
 pub mod product_page;
+pub mod product_restock_page;
+pub mod profile_page;
 pub mod purchases_page;
+pub mod register_page;
+pub mod reset_password_page;
+pub mod settings_page;
+pub mod settlement_page;
+pub mod settlements_page;
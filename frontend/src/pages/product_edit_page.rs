@@ -0,0 +1,182 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_async_with_options, UseAsyncOptions};
+use yew_router::prelude::*;
+
+use crate::{
+    api::{self, ApiError},
+    components::{footer::Footer, navbar::Navbar, product::category_select::CategorySelect},
+    utils::class_if,
+    Route,
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct ProductEditPageProps {
+    pub id: u32,
+}
+
+#[function_component(ProductEditPage)]
+pub fn product_edit_page(props: &ProductEditPageProps) -> Html {
+    let history = use_history().expect("yew-router must be accessible");
+    let name_ref = use_node_ref();
+    let description_ref = use_node_ref();
+    let price_ref = use_node_ref();
+    let category_ref = use_node_ref();
+    let barcode_ref = use_node_ref();
+    let price_change_reason_ref = use_node_ref();
+
+    let product = {
+        let product_id = props.id;
+        use_async_with_options(
+            async move { api::get_product(product_id).await },
+            UseAsyncOptions::enable_auto(),
+        )
+    };
+
+    let state = {
+        let product_id = props.id;
+        let name_ref = name_ref.clone();
+        let description_ref = description_ref.clone();
+        let price_ref = price_ref.clone();
+        let category_ref = category_ref.clone();
+        let barcode_ref = barcode_ref.clone();
+        let price_change_reason_ref = price_change_reason_ref.clone();
+
+        use_async(async move {
+            let product_payload = api::ProductPayload {
+                id: Some(product_id),
+                name: name_ref.cast::<HtmlInputElement>().unwrap().value(),
+                description: Some(description_ref.cast::<HtmlInputElement>().unwrap().value()),
+                stock: None,
+                price: price_ref
+                    .cast::<HtmlInputElement>()
+                    .unwrap()
+                    .value_as_number() as u32,
+                category_id: category_ref
+                    .cast::<HtmlSelectElement>()
+                    .unwrap()
+                    .value()
+                    .parse()
+                    .ok(),
+                barcode: Some(barcode_ref.cast::<HtmlInputElement>().unwrap().value()),
+                price_change_reason: Some(
+                    price_change_reason_ref
+                        .cast::<HtmlInputElement>()
+                        .unwrap()
+                        .value(),
+                ),
+            };
+
+            api::update_product(product_id, &product_payload).await
+        })
+    };
+
+    let image_ref = use_node_ref();
+    let image_upload = {
+        let product_id = props.id;
+        let image_ref = image_ref.clone();
+        let product = product.clone();
+        use_async(async move {
+            let image = image_ref
+                .cast::<HtmlInputElement>()
+                .unwrap()
+                .files()
+                .and_then(|files| files.get(0))
+                .ok_or_else(|| ApiError::GenericError("no image selected".to_string()))?;
+            let res = api::upload_product_image(product_id, image).await;
+            if res.is_ok() {
+                product.run();
+            }
+            res
+        })
+    };
+
+    let handle_image_upload = {
+        let image_upload = image_upload.clone();
+        Callback::from(move |_| {
+            image_upload.run();
+        })
+    };
+
+    let handle_submit = {
+        let state = state.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // prevent form submission
+            state.run();
+        })
+    };
+
+    if state.data.is_some() {
+        history.push(Route::ProductPage);
+    }
+
+    let loading = product.loading || state.loading || image_upload.loading;
+    let error = state
+        .error
+        .as_ref()
+        .or(image_upload.error.as_ref())
+        .or(product.error.as_ref());
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "products-card", class_if(loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        error.map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Edit Product"}
+                    </div>
+                    <div class="card-content">
+                        {
+                            product.data.as_ref().map_or_else(|| html!{}, |product| html! {
+                                <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                                    <label for="product--name">{"Name (*)"}</label>
+                                    <input ref={name_ref} type="text" id="product--name" required={true} value={product.name.clone()} />
+
+                                    <label for="product--description">{"Description"}</label>
+                                    <input ref={description_ref} type="text" id="product--description" value={product.description.clone().unwrap_or_default()} />
+
+                                    <p>
+                                        {format!("{} in stock. ", product.stock)}
+                                        <Link<Route> to={Route::ProductRestockPage { id: product.id }}>{"Restock"}</Link<Route>>
+                                    </p>
+
+                                    <label for="product--price">{"Price (cents) (*)"}</label>
+                                    <input ref={price_ref} type="number" min={0} id="product--price" value={product.price.to_string()} />
+
+                                    <label for="product--image">{"Image"}</label>
+                                    <div class="product-image-upload">
+                                        {
+                                            product.thumbnail_url.as_ref().map_or_else(|| html!{}, |thumbnail_url| html! {
+                                                <img src={thumbnail_url.clone()} alt={product.name.clone()} />
+                                            })
+                                        }
+                                        <input ref={image_ref} type="file" accept="image/png,image/jpeg" id="product--image" />
+                                        <button type="button" onclick={handle_image_upload} disabled={loading} class="btn">{"Upload"}</button>
+                                    </div>
+
+                                    <label for="product--barcode">{"Barcode (EAN-13)"}</label>
+                                    <input ref={barcode_ref} type="text" inputmode="numeric" pattern="[0-9]{13}" id="product--barcode" value={product.barcode.clone().unwrap_or_default()} />
+
+                                    <label for="product--category">{"Category"}</label>
+                                    <CategorySelect select_ref={category_ref} selected={product.category_id} />
+
+                                    <label for="product--price-change-reason">{"Reason for price change"}</label>
+                                    <input ref={price_change_reason_ref} type="text" id="product--price-change-reason" />
+
+                                    <button type="submit" disabled={loading} class="btn btn--full-width">{"Save"}</button>
+                                </form>
+                            })
+                        }
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
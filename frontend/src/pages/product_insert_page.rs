@@ -1,11 +1,11 @@
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew_hooks::use_async;
 use yew_router::prelude::*;
 
 use crate::{
     api,
-    components::{footer::Footer, navbar::Navbar},
+    components::{footer::Footer, navbar::Navbar, product::category_select::CategorySelect},
     utils::class_if,
     Route,
 };
@@ -17,26 +17,40 @@ pub fn product_insert_page() -> Html {
     let description_ref = use_node_ref();
     let stock_ref = use_node_ref();
     let price_ref = use_node_ref();
+    let category_ref = use_node_ref();
+    let barcode_ref = use_node_ref();
 
     let state = {
         let name_ref = name_ref.clone();
         let description_ref = description_ref.clone();
         let stock_ref = stock_ref.clone();
         let price_ref = price_ref.clone();
+        let category_ref = category_ref.clone();
+        let barcode_ref = barcode_ref.clone();
 
         use_async(async move {
             let product_payload = api::ProductPayload {
                 id: None,
                 name: name_ref.cast::<HtmlInputElement>().unwrap().value(),
                 description: Some(description_ref.cast::<HtmlInputElement>().unwrap().value()),
-                stock: stock_ref
-                    .cast::<HtmlInputElement>()
-                    .unwrap()
-                    .value_as_number() as u32,
+                stock: Some(
+                    stock_ref
+                        .cast::<HtmlInputElement>()
+                        .unwrap()
+                        .value_as_number() as u32,
+                ),
                 price: price_ref
                     .cast::<HtmlInputElement>()
                     .unwrap()
                     .value_as_number() as u32,
+                category_id: category_ref
+                    .cast::<HtmlSelectElement>()
+                    .unwrap()
+                    .value()
+                    .parse()
+                    .ok(),
+                barcode: Some(barcode_ref.cast::<HtmlInputElement>().unwrap().value()),
+                price_change_reason: None,
             };
 
             api::insert_product(&product_payload).await
@@ -83,6 +97,12 @@ pub fn product_insert_page() -> Html {
                             <label for="product--price">{"Price (cents) (*)"}</label>
                             <input ref={price_ref} type="number" min={0} id="product--price" />
 
+                            <label for="product--barcode">{"Barcode (EAN-13)"}</label>
+                            <input ref={barcode_ref} type="text" inputmode="numeric" pattern="[0-9]{13}" id="product--barcode" />
+
+                            <label for="product--category">{"Category"}</label>
+                            <CategorySelect select_ref={category_ref} />
+
                             <button type="submit" disabled={state.loading} class="btn btn--full-width">{"Create"}</button>
                         </form>
                     </div>
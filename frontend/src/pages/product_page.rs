@@ -1,23 +1,131 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
-use yew_hooks::{use_async_with_options, UseAsyncOptions};
+use yew_hooks::{use_async, use_async_with_options, UseAsyncOptions};
 
 use crate::{
     api,
-    components::{footer::Footer, navbar::Navbar, product::product_item::ProductItem},
+    components::{
+        footer::Footer,
+        navbar::Navbar,
+        product::{
+            archived_product_item::ArchivedProductItem,
+            barcode_scanner::BarcodeScanner,
+            cart::{Cart, CartItem},
+            product_item::ProductItem,
+        },
+    },
+    hooks::auth::use_auth,
     utils::class_if,
 };
 
 #[function_component(ProductPage)]
 pub fn product_page() -> Html {
-    let products = use_async_with_options(
-        async move { api::list_products().await },
+    let user = use_auth();
+    let query = use_state(api::ProductQuery::default);
+    // Every seller seen so far, so the seller filter keeps its options while filtering
+    let sellers = use_state(Vec::<(u32, String)>::new);
+    let products = {
+        let query = (*query).clone();
+        let sellers = sellers.clone();
+        use_async(async move {
+            let res = api::list_products(&query).await;
+            if let Ok(product_list) = &res {
+                let mut known_sellers = (*sellers).clone();
+                for product in product_list {
+                    if !known_sellers.iter().any(|(id, _)| *id == product.seller_id) {
+                        known_sellers.push((product.seller_id, product.seller_name.clone()));
+                    }
+                }
+                if known_sellers.len() != sellers.len() {
+                    known_sellers.sort_by(|(_, a), (_, b)| a.cmp(b));
+                    sellers.set(known_sellers);
+                }
+            }
+            res
+        })
+    };
+
+    {
+        let products = products.clone();
+        use_effect_with_deps(
+            move |_| {
+                products.run();
+                || {}
+            },
+            (*query).clone(),
+        );
+    }
+
+    let search_ref = use_node_ref();
+    let seller_ref = use_node_ref();
+    let sort_ref = use_node_ref();
+    let out_of_stock_ref = use_node_ref();
+    let handle_query_change = {
+        let query = query.clone();
+        let search_ref = search_ref.clone();
+        let seller_ref = seller_ref.clone();
+        let sort_ref = sort_ref.clone();
+        let out_of_stock_ref = out_of_stock_ref.clone();
+        Callback::from(move |_: Event| {
+            let search = search_ref.cast::<HtmlInputElement>().unwrap().value();
+            let search = search.trim();
+            query.set(api::ProductQuery {
+                search: (!search.is_empty()).then(|| search.to_string()),
+                seller_id: seller_ref
+                    .cast::<HtmlSelectElement>()
+                    .unwrap()
+                    .value()
+                    .parse()
+                    .ok(),
+                sort: api::ProductSort::from_key(
+                    &sort_ref.cast::<HtmlSelectElement>().unwrap().value(),
+                ),
+                include_out_of_stock: out_of_stock_ref
+                    .cast::<HtmlInputElement>()
+                    .unwrap()
+                    .checked(),
+            });
+        })
+    };
+
+    let categories = use_async_with_options(
+        async move { api::list_categories().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    let archived_products = use_async_with_options(
+        async move { api::list_archived_products().await },
         UseAsyncOptions::enable_auto(),
     );
 
     let refresh_products = {
         let products = products.clone();
+        let archived_products = archived_products.clone();
         Callback::<()>::from(move |_| {
             products.run();
+            archived_products.run();
+        })
+    };
+
+    let cart_items = use_state(Vec::<CartItem>::new);
+
+    let cart_change_handler = {
+        let cart_items = cart_items.clone();
+        Callback::from(move |items: Vec<CartItem>| cart_items.set(items))
+    };
+
+    let add_to_cart_handler = {
+        let cart_items = cart_items.clone();
+        Callback::from(move |product: api::Product| {
+            let mut items = (*cart_items).clone();
+            match items.iter_mut().find(|item| item.product.id == product.id) {
+                Some(item) => item.quantity = (item.quantity + 1).min(product.stock),
+                None => items.push(CartItem {
+                    product,
+                    quantity: 1,
+                }),
+            }
+            cart_items.set(items);
         })
     };
 
@@ -25,6 +133,11 @@ pub fn product_page() -> Html {
         <>
             <Navbar />
             <main>
+                <Cart
+                    items={(*cart_items).clone()}
+                    on_change={cart_change_handler}
+                    on_checkout={&refresh_products}
+                />
                 <div class={classes!("card", "products-card", class_if(products.loading, "card-loading"))}>
                     <div class="loading-bar" />
                     {
@@ -36,22 +149,78 @@ pub fn product_page() -> Html {
                         {"Products"}
                     </div>
                     <div class="card-content">
+                        <BarcodeScanner on_update={&refresh_products} />
+                        <div class="product-filter">
+                            <input ref={search_ref} onchange={handle_query_change.clone()} type="search" placeholder="Search" class="product-filter--search" />
+                            <select ref={seller_ref} onchange={handle_query_change.clone()}>
+                                <option value="">{"All sellers"}</option>
+                                {
+                                    sellers.iter()
+                                        .map(|(id, name)| html! {
+                                            <option value={id.to_string()}>{name}</option>
+                                        })
+                                        .collect::<Html>()
+                                }
+                            </select>
+                            <select ref={sort_ref} onchange={handle_query_change.clone()}>
+                                {
+                                    api::ProductSort::ALL.iter()
+                                        .map(|sort| html! {
+                                            <option value={sort.key()}>{format!("Sort by {}", sort.to_string().to_lowercase())}</option>
+                                        })
+                                        .collect::<Html>()
+                                }
+                            </select>
+                            <label>
+                                <input ref={out_of_stock_ref} onchange={handle_query_change} type="checkbox" />
+                                {"Include out of stock"}
+                            </label>
+                        </div>
                         <div class="product-list">
                             {
                                 products.data.as_ref().map_or_else(|| html!{}, |product_list| {
-                                    if product_list.is_empty() {
+                                    if product_list.is_empty() && *query == api::ProductQuery::default() {
                                         html! {
                                             <p>{"There are no products in stock"}</p>
                                         }
+                                    } else if product_list.is_empty() {
+                                        html! {
+                                            <p>{"No products match your search"}</p>
+                                        }
                                     } else {
-                                        product_list.iter()
-                                            .map(|product| {
+                                        let sections = group_by_category(product_list, categories.data.as_deref().unwrap_or_default());
+                                        let show_titles = sections.iter().any(|(category, _)| category.is_some());
+                                        sections.into_iter()
+                                            .map(|(category, section_products)| {
                                                 html! {
-                                                    <ProductItem
-                                                        key={product.id}
-                                                        product={product.clone()}
-                                                        on_update={&refresh_products}
-                                                    />
+                                                    <>
+                                                        {
+                                                            if show_titles {
+                                                                html! {
+                                                                    <h3 class="product-section--title">
+                                                                        {category.map_or_else(|| "Other".to_string(), |category| category.name.clone())}
+                                                                    </h3>
+                                                                }
+                                                            } else {
+                                                                html! {}
+                                                            }
+                                                        }
+                                                        {
+                                                            section_products.into_iter()
+                                                                .map(|product| {
+                                                                    html! {
+                                                                        <ProductItem
+                                                                            key={product.id}
+                                                                            product={product.clone()}
+                                                                            is_seller={user.as_ref().map_or(false, |user| user.id == product.seller_id)}
+                                                                            on_update={&refresh_products}
+                                                                            on_add_to_cart={&add_to_cart_handler}
+                                                                        />
+                                                                    }
+                                                                })
+                                                                .collect::<Html>()
+                                                        }
+                                                    </>
                                                 }
                                             })
                                             .collect()
@@ -61,9 +230,73 @@ pub fn product_page() -> Html {
                         </div>
                     </div>
                 </div>
+                {
+                    archived_products.data.as_ref().map_or_else(|| html!{}, |product_list| {
+                        if product_list.is_empty() {
+                            html! {}
+                        } else {
+                            html! {
+                                <div class={classes!("card", "products-card", class_if(archived_products.loading, "card-loading"))}>
+                                    <div class="loading-bar" />
+                                    <div class="card-header">
+                                        {"Archived Products"}
+                                    </div>
+                                    <div class="card-content">
+                                        <div class="product-list">
+                                            {
+                                                product_list.iter()
+                                                    .map(|product| {
+                                                        html! {
+                                                            <ArchivedProductItem
+                                                                key={product.id}
+                                                                product={product.clone()}
+                                                                on_update={&refresh_products}
+                                                            />
+                                                        }
+                                                    })
+                                                    .collect::<Html>()
+                                            }
+                                        </div>
+                                    </div>
+                                </div>
+                            }
+                        }
+                    })
+                }
                 <a class="fab" href="/product/insert">{"+"}</a>
             </main>
             <Footer />
         </>
     }
 }
+
+/// Splits the products into one section per category, in the order of the categories,
+/// with uncategorized products last
+fn group_by_category<'a>(
+    products: &'a [api::Product],
+    categories: &'a [api::Category],
+) -> Vec<(Option<&'a api::Category>, Vec<&'a api::Product>)> {
+    let mut sections: Vec<(Option<&api::Category>, Vec<&api::Product>)> = categories
+        .iter()
+        .map(|category| {
+            let section_products = products
+                .iter()
+                .filter(|product| product.category_id == Some(category.id))
+                .collect();
+            (Some(category), section_products)
+        })
+        .collect();
+
+    let uncategorized: Vec<&api::Product> = products
+        .iter()
+        .filter(|product| {
+            product.category_id.map_or(true, |category_id| {
+                !categories.iter().any(|category| category.id == category_id)
+            })
+        })
+        .collect();
+    sections.push((None, uncategorized));
+
+    sections.retain(|(_, section_products)| !section_products.is_empty());
+    sections
+}
@@ -0,0 +1,136 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_async_with_options, UseAsyncOptions};
+use yew_router::prelude::*;
+
+use crate::{
+    api,
+    components::{footer::Footer, navbar::Navbar},
+    utils::class_if,
+    Route,
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct ProductRestockPageProps {
+    pub id: u32,
+}
+
+#[function_component(ProductRestockPage)]
+pub fn product_restock_page(props: &ProductRestockPageProps) -> Html {
+    let history = use_history().expect("yew-router must be accessible");
+    let quantity_ref = use_node_ref();
+    let remove_quantity_ref = use_node_ref();
+
+    let product = {
+        let product_id = props.id;
+        use_async_with_options(
+            async move { api::get_product(product_id).await },
+            UseAsyncOptions::enable_auto(),
+        )
+    };
+
+    let state = {
+        let product_id = props.id;
+        let quantity_ref = quantity_ref.clone();
+
+        use_async(async move {
+            let payload = api::RestockProductPayload {
+                quantity: quantity_ref
+                    .cast::<HtmlInputElement>()
+                    .unwrap()
+                    .value_as_number() as u32,
+            };
+
+            api::restock_product(product_id, &payload).await
+        })
+    };
+
+    let remove = {
+        let product_id = props.id;
+        let remove_quantity_ref = remove_quantity_ref.clone();
+
+        use_async(async move {
+            let quantity = remove_quantity_ref
+                .cast::<HtmlInputElement>()
+                .unwrap()
+                .value_as_number() as i64;
+            let payload = api::AdjustStockPayload { delta: -quantity };
+
+            api::adjust_stock(product_id, &payload).await
+        })
+    };
+
+    let handle_submit = {
+        let state = state.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // prevent form submission
+            state.run();
+        })
+    };
+
+    let handle_remove = {
+        let remove = remove.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // prevent form submission
+            remove.run();
+        })
+    };
+
+    if state.data.is_some() || remove.data.is_some() {
+        history.push(Route::ProductPage);
+    }
+
+    let loading = product.loading || state.loading || remove.loading;
+    let error = state
+        .error
+        .as_ref()
+        .or(remove.error.as_ref())
+        .or(product.error.as_ref());
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "products-card", class_if(loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        error.map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Restock Product"}
+                    </div>
+                    <div class="card-content">
+                        {
+                            product.data.as_ref().map_or_else(|| html!{}, |product| html! {
+                                <div class="product-item">
+                                    <div class="product-info">
+                                        <div class="product-info--name">{product.name.clone()}</div>
+                                        <div class="product-info--metadata">
+                                            <span class="product-info--stock">{product.stock}</span>
+                                            {" in stock"}
+                                        </div>
+                                    </div>
+                                </div>
+                            })
+                        }
+                        <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                            <label for="product--quantity">{"Quantity to add (*)"}</label>
+                            <input ref={quantity_ref} type="number" min={1} id="product--quantity" required={true} />
+
+                            <button type="submit" disabled={loading} class="btn btn--full-width">{"Restock"}</button>
+                        </form>
+                        <form class="form form-vertical form-margin-top" onsubmit={handle_remove}>
+                            <label for="product--remove-quantity">{"Quantity to remove, e.g. expired or missing items (*)"}</label>
+                            <input ref={remove_quantity_ref} type="number" min={1} id="product--remove-quantity" required={true} />
+
+                            <button type="submit" disabled={loading} class="btn btn--full-width">{"Remove from stock"}</button>
+                        </form>
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
@@ -0,0 +1,81 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{
+    api,
+    components::{footer::Footer, navbar::Navbar},
+    hooks::auth::use_auth,
+    utils::{class_if, PHONE_PLACEHOLDER},
+};
+
+#[function_component(ProfilePage)]
+pub fn profile_page() -> Html {
+    let user = use_auth();
+    let name_ref = use_node_ref();
+    let phone_ref = use_node_ref();
+    let update = {
+        let name_ref = name_ref.clone();
+        let phone_ref = phone_ref.clone();
+        use_async(async move {
+            let payload = api::UpdateUserInfoPayload {
+                name: Some(name_ref.cast::<HtmlInputElement>().unwrap().value()),
+                phone_number: Some(phone_ref.cast::<HtmlInputElement>().unwrap().value()),
+            };
+            api::update_user_info(&payload).await
+        })
+    };
+
+    let handle_submit = {
+        let update = update.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // avoid form submission
+            update.run();
+        })
+    };
+
+    // show what was last saved, the logged in user is only fetched once
+    let user = update.data.clone().or(user);
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "login-card", class_if(user.is_none() || update.loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        update.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Profile"}
+                    </div>
+                    <div class="card-content">
+                        {
+                            if update.data.is_some() {
+                                html! { <p>{"Your profile has been updated. If you changed your phone number, use the new one to log in."}</p> }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        {
+                            user.map_or_else(|| html!{}, |user| html! {
+                                <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                                    <label for="profile--name">{"Name"}</label>
+                                    <input ref={name_ref} type="text" id="profile--name" required={true} maxlength="30" value={user.name} />
+
+                                    <label for="profile--phone">{"Phone Number"}</label>
+                                    <input ref={phone_ref} type="tel" id="profile--phone" required={true} placeholder={PHONE_PLACEHOLDER} value={user.phone_number} />
+
+                                    <button type="submit" disabled={update.loading} class="btn btn--full-width btn--primary">{"Save"}</button>
+                                </form>
+                            })
+                        }
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
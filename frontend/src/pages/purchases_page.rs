@@ -1,8 +1,9 @@
 use yew::prelude::*;
 
 use crate::components::{
-    footer::Footer, navbar::Navbar, purchase::purchases_list::PurchasesList,
-    purchase::seller_summary::SellerSummary,
+    footer::Footer, navbar::Navbar, purchase::buyer_summary::BuyerSummary,
+    purchase::purchases_list::PurchasesList, purchase::seller_summary::SellerSummary,
+    purchase::wallets_list::WalletsList,
 };
 
 #[function_component(PurchasesPage)]
@@ -12,6 +13,8 @@ pub fn purchases_page() -> Html {
             <Navbar />
             <main>
                 <SellerSummary />
+                <BuyerSummary />
+                <WalletsList />
                 <PurchasesList />
             </main>
             <Footer />
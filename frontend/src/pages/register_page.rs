@@ -0,0 +1,95 @@
+use serde::Deserialize;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+use yew_router::prelude::*;
+
+use crate::{
+    api,
+    utils::{class_if, PHONE_PLACEHOLDER},
+    Route,
+};
+
+#[derive(Default, Deserialize)]
+struct RegisterQuery {
+    #[serde(default)]
+    code: String,
+}
+
+#[function_component(RegisterPage)]
+pub fn register_page() -> Html {
+    let history = use_history().expect("yew-router must be accessible");
+    let invite_code = use_location()
+        .and_then(|location| location.query::<RegisterQuery>().ok())
+        .unwrap_or_default()
+        .code;
+    let name_ref = use_node_ref();
+    let phone_ref = use_node_ref();
+    let password_ref = use_node_ref();
+    let invite_code_ref = use_node_ref();
+    let state = {
+        let name_ref = name_ref.clone();
+        let phone_ref = phone_ref.clone();
+        let password_ref = password_ref.clone();
+        let invite_code_ref = invite_code_ref.clone();
+        use_async(async move {
+            let payload = &api::RegisterPayload {
+                name: name_ref.cast::<HtmlInputElement>().unwrap().value(),
+                phone_number: phone_ref.cast::<HtmlInputElement>().unwrap().value(),
+                password: password_ref.cast::<HtmlInputElement>().unwrap().value(),
+                invite_code: invite_code_ref.cast::<HtmlInputElement>().unwrap().value(),
+            };
+
+            api::register(payload).await
+        })
+    };
+
+    if state.data.is_some() {
+        history.push(Route::Login);
+    }
+
+    let handle_submit = {
+        let state = state.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // avoid form submission
+            state.run();
+        })
+    };
+
+    html! {
+        <main>
+            <div class={classes!("card", "login-card", class_if(state.loading, "card-loading"))}>
+                <div class="loading-bar" />
+                {
+                    state.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="card-error">{error}</div>
+                    })
+                }
+                <div class="card-header">
+                    {"Join Frigu"}
+                </div>
+                <div class="card-content">
+                    <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                        <label for="register--invite-code">{"Invite Code"}</label>
+                        <input ref={invite_code_ref} type="text" id="register--invite-code" required={true} value={invite_code} />
+
+                        <label for="register--name">{"Name"}</label>
+                        <input ref={name_ref} type="text" id="register--name" required={true} maxlength="30" />
+
+                        <label for="register--phone">{"Phone Number"}</label>
+                        <input ref={phone_ref} type="tel" id="register--phone" required={true} placeholder={PHONE_PLACEHOLDER} />
+
+                        <label for="register--password">{"Password"}</label>
+                        <input ref={password_ref} type="password" id="register--password" required={true} minlength="8" />
+
+                        <button type="submit" disabled={state.loading} class="btn btn--full-width btn--primary">{"Register"}</button>
+                    </form>
+                    <p class="login-alternative">
+                        {"Already have an account? "}
+                        <Link<Route> to={Route::Login}>{"Login"}</Link<Route>>
+                    </p>
+                </div>
+            </div>
+        </main>
+    }
+}
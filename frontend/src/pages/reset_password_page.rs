@@ -0,0 +1,84 @@
+use serde::Deserialize;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+use yew_router::prelude::*;
+
+use crate::{api, utils::class_if, Route};
+
+#[derive(Default, Deserialize)]
+struct ResetPasswordQuery {
+    #[serde(default)]
+    token: String,
+}
+
+#[function_component(ResetPasswordPage)]
+pub fn reset_password_page() -> Html {
+    let history = use_history().expect("yew-router must be accessible");
+    let token = use_location()
+        .and_then(|location| location.query::<ResetPasswordQuery>().ok())
+        .unwrap_or_default()
+        .token;
+    let password_ref = use_node_ref();
+    let state = {
+        let token = token.clone();
+        let password_ref = password_ref.clone();
+        use_async(async move {
+            let payload = &api::ResetPasswordPayload {
+                token,
+                password: password_ref.cast::<HtmlInputElement>().unwrap().value(),
+            };
+
+            api::reset_password(payload).await
+        })
+    };
+
+    if state.data.is_some() {
+        history.push(Route::Login);
+    }
+
+    let handle_submit = {
+        let state = state.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // avoid form submission
+            state.run();
+        })
+    };
+
+    html! {
+        <main>
+            <div class={classes!("card", "login-card", class_if(state.loading, "card-loading"))}>
+                <div class="loading-bar" />
+                {
+                    state.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                        <div class="card-error">{error}</div>
+                    })
+                }
+                <div class="card-header">
+                    {"Reset Password"}
+                </div>
+                <div class="card-content">
+                    {
+                        if token.is_empty() {
+                            html! {
+                                <p>{"This link is missing its reset token. Ask an admin for a new one."}</p>
+                            }
+                        } else {
+                            html! {
+                                <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                                    <label for="reset-password--password">{"New Password"}</label>
+                                    <input ref={password_ref} type="password" id="reset-password--password" required={true} minlength="8" />
+
+                                    <button type="submit" disabled={state.loading} class="btn btn--full-width btn--primary">{"Set password"}</button>
+                                </form>
+                            }
+                        }
+                    }
+                    <p class="login-alternative">
+                        <Link<Route> to={Route::Login}>{"Back to login"}</Link<Route>>
+                    </p>
+                </div>
+            </div>
+        </main>
+    }
+}
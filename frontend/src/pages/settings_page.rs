@@ -0,0 +1,92 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::{
+    api::{self, ApiError},
+    components::{footer::Footer, navbar::Navbar},
+    hooks::auth::use_auth,
+    utils::class_if,
+};
+
+#[function_component(SettingsPage)]
+pub fn settings_page() -> Html {
+    use_auth();
+    let old_password_ref = use_node_ref();
+    let new_password_ref = use_node_ref();
+    let confirm_password_ref = use_node_ref();
+    let change_password = {
+        let old_password_ref = old_password_ref.clone();
+        let new_password_ref = new_password_ref.clone();
+        let confirm_password_ref = confirm_password_ref.clone();
+        use_async(async move {
+            let inputs = [&old_password_ref, &new_password_ref, &confirm_password_ref]
+                .map(|input_ref| input_ref.cast::<HtmlInputElement>().unwrap());
+            if inputs[1].value() != inputs[2].value() {
+                return Err(ApiError::GenericError(
+                    "new passwords don't match".to_string(),
+                ));
+            }
+            let payload = api::ChangePasswordPayload {
+                old_password: inputs[0].value(),
+                new_password: inputs[1].value(),
+            };
+            let res = api::change_password(&payload).await;
+            if res.is_ok() {
+                for input in inputs {
+                    input.set_value("");
+                }
+            }
+            res
+        })
+    };
+
+    let handle_submit = {
+        let change_password = change_password.clone();
+        Callback::from(move |event: FocusEvent| {
+            event.prevent_default(); // avoid form submission
+            change_password.run();
+        })
+    };
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "login-card", class_if(change_password.loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        change_password.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Change Password"}
+                    </div>
+                    <div class="card-content">
+                        {
+                            if change_password.data.is_some() {
+                                html! { <p>{"Your password has been changed."}</p> }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        <form class="form form-vertical form-margin-top" onsubmit={handle_submit}>
+                            <label for="settings--old-password">{"Current Password"}</label>
+                            <input ref={old_password_ref} type="password" id="settings--old-password" required={true} />
+
+                            <label for="settings--new-password">{"New Password"}</label>
+                            <input ref={new_password_ref} type="password" id="settings--new-password" required={true} minlength="8" />
+
+                            <label for="settings--confirm-password">{"Confirm New Password"}</label>
+                            <input ref={confirm_password_ref} type="password" id="settings--confirm-password" required={true} minlength="8" />
+
+                            <button type="submit" disabled={change_password.loading} class="btn btn--full-width btn--primary">{"Change password"}</button>
+                        </form>
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
@@ -0,0 +1,134 @@
+use yew::prelude::*;
+use yew_hooks::{use_async, use_async_with_options, UseAsyncOptions};
+use yew_router::prelude::*;
+
+use crate::{
+    api,
+    components::{footer::Footer, navbar::Navbar, purchase::receipt_item::ReceiptItem},
+    hooks::auth::use_auth,
+    utils::{self, class_if, format_datetime},
+    Route,
+};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct SettlementPageProps {
+    pub id: u32,
+}
+
+#[function_component(SettlementPage)]
+pub fn settlement_page(props: &SettlementPageProps) -> Html {
+    let history = use_history().expect("yew-router must be accessible");
+    let user = use_auth();
+
+    let settlement = {
+        let settlement_id = props.id;
+        use_async_with_options(
+            async move { api::get_settlement(settlement_id).await },
+            UseAsyncOptions::enable_auto(),
+        )
+    };
+
+    let refresh_settlement = {
+        let settlement = settlement.clone();
+        Callback::<()>::from(move |_| {
+            settlement.run();
+        })
+    };
+
+    let revert = {
+        let settlement_id = props.id;
+        use_async(async move { api::revert_settlement(settlement_id).await })
+    };
+
+    let handle_revert = {
+        let revert = revert.clone();
+        Callback::from(move |_| {
+            revert.run();
+        })
+    };
+
+    if revert.data.is_some() {
+        history.push(Route::SettlementsPage);
+    }
+
+    let loading = settlement.loading || revert.loading;
+    let error = revert.error.as_ref().or(settlement.error.as_ref());
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "purchases-card", class_if(loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        error.map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Receipt"}
+                    </div>
+                    {
+                        settlement.data.as_ref().map_or_else(|| html!{}, |settlement| {
+                            let is_seller = user.as_ref().map_or(false, |user| user.id == settlement.seller.id);
+                            html! {
+                                <div class="card-content receipt">
+                                    <div class="receipt--metadata">
+                                        <div>{format!("Seller: {}", settlement.seller.name)}</div>
+                                        <div>{format!("Buyer: {}", settlement.buyer.name)}</div>
+                                        <div>{format!("Payment method: {}", settlement.payment_method)}</div>
+                                        {
+                                            if settlement.credit_used > 0 {
+                                                html! {
+                                                    <div>{format!("Paid from credit: {}", utils::format_display_price(settlement.credit_used))}</div>
+                                                }
+                                            } else {
+                                                html! {}
+                                            }
+                                        }
+                                        <div>{format!("Date: {}", format_datetime(settlement.date))}</div>
+                                    </div>
+                                    <div class="purchases-list">
+                                        {
+                                            settlement.purchases.iter()
+                                                .flatten()
+                                                .map(|purchase| {
+                                                    html! {
+                                                        <ReceiptItem
+                                                            key={purchase.id}
+                                                            purchase={purchase.clone()}
+                                                            is_seller={is_seller}
+                                                            on_update={&refresh_settlement}
+                                                        />
+                                                    }
+                                                })
+                                                .collect::<Html>()
+                                        }
+                                    </div>
+                                    <div class="receipt--total">
+                                        <span>{"Total"}</span>
+                                        <span>{utils::format_display_price(settlement.amount)}</span>
+                                    </div>
+                                    {
+                                        if is_seller {
+                                            html! {
+                                                <div class="card-actions">
+                                                    <button onclick={handle_revert} disabled={loading} class="btn settlement-actions--revert">
+                                                        {"Revert settlement"}
+                                                    </button>
+                                                </div>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+                                </div>
+                            }
+                        })
+                    }
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
@@ -0,0 +1,83 @@
+use yew::prelude::*;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+use yew_router::prelude::*;
+
+use crate::{
+    api,
+    components::{footer::Footer, navbar::Navbar},
+    hooks::auth::use_auth,
+    utils::{self, class_if, format_datetime},
+    Route,
+};
+
+#[function_component(SettlementsPage)]
+pub fn settlements_page() -> Html {
+    let user = use_auth();
+    let settlements = use_async_with_options(
+        async move { api::list_settlements().await },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    html! {
+        <>
+            <Navbar />
+            <main>
+                <div class={classes!("card", "purchases-card", class_if(settlements.loading, "card-loading"))}>
+                    <div class="loading-bar" />
+                    {
+                        settlements.error.as_ref().map_or_else(|| html!{}, |error| html! {
+                            <div class="card-error">{error}</div>
+                        })
+                    }
+                    <div class="card-header">
+                        {"Settlements"}
+                    </div>
+                    <div class="card-content">
+                        <div class="purchases-list">
+                            {
+                                settlements.data.as_ref().map_or_else(|| html!{}, |settlements| {
+                                    if settlements.is_empty() {
+                                        html! {
+                                            <p>{"There are no settlements yet"}</p>
+                                        }
+                                    } else {
+                                        settlements.iter()
+                                            .map(|settlement| {
+                                                let is_seller = user.as_ref().map_or(false, |user| user.id == settlement.seller.id);
+                                                html! {
+                                                    <div class="settlement-item" key={settlement.id}>
+                                                        <div class="settlement-info">
+                                                            <div class="settlement-info--name">
+                                                                {
+                                                                    if is_seller {
+                                                                        format!("Received from {}", settlement.buyer.name)
+                                                                    } else {
+                                                                        format!("Paid to {}", settlement.seller.name)
+                                                                    }
+                                                                }
+                                                            </div>
+                                                            <div class="settlement-info--metadata">
+                                                                {format!("{} | {}", settlement.payment_method, format_datetime(settlement.date))}
+                                                            </div>
+                                                        </div>
+                                                        <div class="settlement-amount">
+                                                            {utils::format_display_price(settlement.amount)}
+                                                        </div>
+                                                        <Link<Route> to={Route::SettlementPage { id: settlement.id }} classes={classes!("btn", "settlement-actions--receipt")}>
+                                                            {"Receipt"}
+                                                        </Link<Route>>
+                                                    </div>
+                                                }
+                                            })
+                                            .collect()
+                                    }
+                                })
+                            }
+                        </div>
+                    </div>
+                </div>
+            </main>
+            <Footer />
+        </>
+    }
+}
@@ -1,13 +1,29 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+
+/// Local numbers work without a country code, foreign ones need it
+pub const PHONE_PLACEHOLDER: &str = "912 345 678 or +44 7700 900123";
 
 pub fn format_display_price(price: u32) -> String {
     format!("{}.{:02}€", price / 100, price % 100)
 }
 
+/// Like a price, but negative when the buyer owes the seller
+pub fn format_display_balance(balance: i64) -> String {
+    let sign = if balance < 0 { "-" } else { "" };
+    let balance = balance.unsigned_abs();
+    format!("{}{}.{:02}€", sign, balance / 100, balance % 100)
+}
+
 pub fn format_datetime(datetime: DateTime<Local>) -> String {
     datetime.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
+/// Parses the value of a date input as the start of that day in local time
+pub fn parse_date_input(value: &str) -> Option<DateTime<Local>> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Local.from_local_datetime(&date.and_hms(0, 0, 0)).single()
+}
+
 pub fn class_if(cond: bool, class: &str) -> Option<&str> {
     if cond {
         Some(class)
@@ -1,6 +1,13 @@
 pub use sea_orm;
 pub mod prelude;
 
+pub mod category;
+pub mod invite;
+pub mod password_reset;
+pub mod payment;
 pub mod product;
+pub mod product_price_history;
 pub mod purchase;
+pub mod refund;
+pub mod settlement;
 pub mod user;
@@ -1,5 +1,12 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.7.0
 
+pub use super::category::Entity as Category;
+pub use super::invite::Entity as Invite;
+pub use super::password_reset::Entity as PasswordReset;
+pub use super::payment::Entity as Payment;
 pub use super::product::Entity as Product;
+pub use super::product_price_history::Entity as ProductPriceHistory;
 pub use super::purchase::Entity as Purchase;
+pub use super::refund::Entity as Refund;
+pub use super::settlement::Entity as Settlement;
 pub use super::user::Entity as User;
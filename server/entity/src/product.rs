@@ -12,6 +12,11 @@ pub struct Model {
     pub seller: u32,
     pub stock: u32,
     pub price: u32,
+    pub archived: bool,
+    pub category: Option<u32>,
+    pub image: Option<String>,
+    #[sea_orm(unique)]
+    pub barcode: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -24,6 +29,16 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::category::Entity",
+        from = "Column::Category",
+        to = "super::category::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Category,
+    #[sea_orm(has_many = "super::product_price_history::Entity")]
+    ProductPriceHistory,
     #[sea_orm(has_many = "super::purchase::Entity")]
     Purchase,
 }
@@ -34,6 +49,18 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::category::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl Related<super::product_price_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductPriceHistory.def()
+    }
+}
+
 impl Related<super::purchase::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Purchase.def()
@@ -13,6 +13,7 @@ pub struct Model {
     pub unit_price: u32,
     pub date: DateTimeUtc,
     pub paid_date: Option<DateTimeUtc>,
+    pub settlement: Option<u32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -33,6 +34,16 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     Product,
+    #[sea_orm(has_many = "super::refund::Entity")]
+    Refund,
+    #[sea_orm(
+        belongs_to = "super::settlement::Entity",
+        from = "Column::Settlement",
+        to = "super::settlement::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Settlement,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -47,6 +58,18 @@ impl Related<super::product::Entity> for Entity {
     }
 }
 
+impl Related<super::refund::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Refund.def()
+    }
+}
+
+impl Related<super::settlement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Settlement.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {
     // Will be triggered before insert / update
     fn before_save(self, insert: bool) -> Result<Self, DbErr> {
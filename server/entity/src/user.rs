@@ -11,12 +11,16 @@ pub struct Model {
     #[sea_orm(unique)]
     pub phone_number: String,
     pub hashed_password: String,
+    pub role: String,
+    pub disabled: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::product::Entity")]
     Product,
+    #[sea_orm(has_many = "super::product_price_history::Entity")]
+    ProductPriceHistory,
     #[sea_orm(has_many = "super::purchase::Entity")]
     Purchase,
 }
@@ -27,6 +31,12 @@ impl Related<super::product::Entity> for Entity {
     }
 }
 
+impl Related<super::product_price_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductPriceHistory.def()
+    }
+}
+
 impl Related<super::purchase::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Purchase.def()
@@ -1,12 +1,42 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20220501_000001_add_product_archived;
+mod m20220502_000001_create_product_price_history;
+mod m20220503_000001_create_refund;
+mod m20220504_000001_create_settlement;
+mod m20220505_000001_add_settlement_details;
+mod m20220506_000001_create_payment;
+mod m20220507_000001_create_category;
+mod m20220508_000001_add_product_image;
+mod m20220509_000001_add_product_barcode;
+mod m20220510_000001_create_invite;
+mod m20220511_000001_add_user_role;
+mod m20220512_000001_create_password_reset;
+mod m20220513_000001_normalize_phone_numbers;
+mod m20220514_000001_add_refund_credited;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20220501_000001_add_product_archived::Migration),
+            Box::new(m20220502_000001_create_product_price_history::Migration),
+            Box::new(m20220503_000001_create_refund::Migration),
+            Box::new(m20220504_000001_create_settlement::Migration),
+            Box::new(m20220505_000001_add_settlement_details::Migration),
+            Box::new(m20220506_000001_create_payment::Migration),
+            Box::new(m20220507_000001_create_category::Migration),
+            Box::new(m20220508_000001_add_product_image::Migration),
+            Box::new(m20220509_000001_add_product_barcode::Migration),
+            Box::new(m20220510_000001_create_invite::Migration),
+            Box::new(m20220511_000001_add_user_role::Migration),
+            Box::new(m20220512_000001_create_password_reset::Migration),
+            Box::new(m20220513_000001_normalize_phone_numbers::Migration),
+            Box::new(m20220514_000001_add_refund_credited::Migration),
+        ]
     }
 }
@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220502_000001_create_product_price_history"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductPriceHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProductPriceHistory::Id)
+                            .unsigned()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductPriceHistory::Product)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductPriceHistory::OldPrice)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductPriceHistory::NewPrice)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductPriceHistory::Date)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductPriceHistory::Actor)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProductPriceHistory::Reason).string())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-product_price_history-product")
+                            .from(ProductPriceHistory::Table, ProductPriceHistory::Product)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-product_price_history-actor")
+                            .from(ProductPriceHistory::Table, ProductPriceHistory::Actor)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductPriceHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum Product {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum ProductPriceHistory {
+    Table,
+    Id,
+    Product,
+    OldPrice,
+    NewPrice,
+    Date,
+    Actor,
+    Reason,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220503_000001_create_refund"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Refund::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Refund::Id)
+                            .unsigned()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Refund::Purchase).unsigned().not_null())
+                    .col(ColumnDef::new(Refund::Amount).unsigned().not_null())
+                    .col(ColumnDef::new(Refund::Reason).string().not_null())
+                    .col(
+                        ColumnDef::new(Refund::ReturnedQuantity)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Refund::Date).date_time().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-refund-purchase")
+                            .from(Refund::Table, Refund::Purchase)
+                            .to(Purchase::Table, Purchase::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Refund::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Purchase {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum Refund {
+    Table,
+    Id,
+    Purchase,
+    Amount,
+    Reason,
+    ReturnedQuantity,
+    Date,
+}
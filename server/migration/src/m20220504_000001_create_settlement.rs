@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220504_000001_create_settlement"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Settlement::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Settlement::Id)
+                            .unsigned()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Settlement::Seller).unsigned().not_null())
+                    .col(ColumnDef::new(Settlement::Buyer).unsigned().not_null())
+                    .col(ColumnDef::new(Settlement::Date).date_time().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-settlement-seller")
+                            .from(Settlement::Table, Settlement::Seller)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-settlement-buyer")
+                            .from(Settlement::Table, Settlement::Buyer)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Purchase::Table)
+                    .add_column(ColumnDef::new(Purchase::Settlement).unsigned())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-purchase-settlement")
+                    .from(Purchase::Table, Purchase::Settlement)
+                    .to(Settlement::Table, Settlement::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk-purchase-settlement")
+                    .table(Purchase::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Purchase::Table)
+                    .drop_column(Purchase::Settlement)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Settlement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum Purchase {
+    Table,
+    Settlement,
+}
+
+#[derive(Iden)]
+pub enum Settlement {
+    Table,
+    Id,
+    Seller,
+    Buyer,
+    Date,
+}
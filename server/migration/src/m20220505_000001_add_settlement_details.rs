@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220505_000001_add_settlement_details"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Settlement::Table)
+                    .add_column(
+                        ColumnDef::new(Settlement::Amount)
+                            .unsigned()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Settlement::PaymentMethod)
+                            .string()
+                            .not_null()
+                            .default("cash"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // settlements created before this migration don't have an amount yet
+        let sql = r#"
+            UPDATE `settlement` SET `amount` = (
+                SELECT COALESCE(SUM(`purchase`.`quantity` * `purchase`.`unit_price`), 0)
+                FROM `purchase`
+                WHERE `purchase`.`settlement` = `settlement`.`id`
+            ) - (
+                SELECT COALESCE(SUM(`refund`.`amount`), 0)
+                FROM `refund`
+                INNER JOIN `purchase` ON `refund`.`purchase` = `purchase`.`id`
+                WHERE `purchase`.`settlement` = `settlement`.`id`
+            )
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Settlement::Table)
+                    .drop_column(Settlement::Amount)
+                    .drop_column(Settlement::PaymentMethod)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Settlement {
+    Table,
+    Amount,
+    PaymentMethod,
+}
@@ -0,0 +1,103 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220506_000001_create_payment"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Payment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Payment::Id)
+                            .unsigned()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Payment::Buyer).unsigned().not_null())
+                    .col(ColumnDef::new(Payment::Seller).unsigned().not_null())
+                    .col(ColumnDef::new(Payment::Amount).unsigned().not_null())
+                    .col(ColumnDef::new(Payment::PaymentMethod).string().not_null())
+                    .col(ColumnDef::new(Payment::Date).date_time().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-payment-buyer")
+                            .from(Payment::Table, Payment::Buyer)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-payment-seller")
+                            .from(Payment::Table, Payment::Seller)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Settlement::Table)
+                    .add_column(
+                        ColumnDef::new(Settlement::CreditUsed)
+                            .unsigned()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Settlement::Table)
+                    .drop_column(Settlement::CreditUsed)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Payment::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum Settlement {
+    Table,
+    CreditUsed,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Iden)]
+pub enum Payment {
+    Table,
+    Id,
+    Buyer,
+    Seller,
+    Amount,
+    PaymentMethod,
+    Date,
+}
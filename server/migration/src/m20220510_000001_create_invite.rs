@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220510_000001_create_invite"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invite::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Invite::Id)
+                            .unsigned()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Invite::Code)
+                            .string_len(16)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Invite::CreatedBy).unsigned())
+                    .col(ColumnDef::new(Invite::CreatedDate).date_time().not_null())
+                    .col(ColumnDef::new(Invite::ExpiryDate).date_time().not_null())
+                    .col(ColumnDef::new(Invite::UsedBy).unsigned())
+                    .col(ColumnDef::new(Invite::UsedDate).date_time())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invite-created_by")
+                            .from(Invite::Table, Invite::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invite-used_by")
+                            .from(Invite::Table, Invite::UsedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Invite::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum Invite {
+    Table,
+    Id,
+    Code,
+    CreatedBy,
+    CreatedDate,
+    ExpiryDate,
+    UsedBy,
+    UsedDate,
+}
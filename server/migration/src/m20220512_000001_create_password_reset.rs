@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220512_000001_create_password_reset"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordReset::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PasswordReset::Id)
+                            .unsigned()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PasswordReset::TokenHash)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(PasswordReset::User).unsigned().not_null())
+                    .col(ColumnDef::new(PasswordReset::CreatedBy).unsigned())
+                    .col(
+                        ColumnDef::new(PasswordReset::CreatedDate)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PasswordReset::ExpiryDate)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PasswordReset::UsedDate).date_time())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-password_reset-user")
+                            .from(PasswordReset::Table, PasswordReset::User)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-password_reset-created_by")
+                            .from(PasswordReset::Table, PasswordReset::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordReset::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum PasswordReset {
+    Table,
+    Id,
+    TokenHash,
+    User,
+    CreatedBy,
+    CreatedDate,
+    ExpiryDate,
+    UsedDate,
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220513_000001_normalize_phone_numbers"
+    }
+}
+
+/// Prefix of the numbers saved before the country code was stored with them.
+/// Must match the `DEFAULT_COUNTRY_CODE` of the server.
+fn default_prefix() -> Result<String, DbErr> {
+    let country_code = std::env::var("DEFAULT_COUNTRY_CODE").unwrap_or_else(|_| "351".to_owned());
+    if country_code.is_empty() || !country_code.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(DbErr::Custom(
+            "DEFAULT_COUNTRY_CODE must only have digits".to_owned(),
+        ));
+    }
+    Ok(format!("+{}", country_code))
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // numbers used to be national numbers of the default country, e.g. 912345678
+        let sql = r#"
+            UPDATE `user` SET `phone_number` = CONCAT(?, `phone_number`)
+            WHERE `phone_number` NOT LIKE '+%'
+        "#;
+        let stmt = Statement::from_sql_and_values(
+            manager.get_database_backend(),
+            sql,
+            vec![default_prefix()?.into()],
+        );
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // foreign numbers can't be told apart from national ones without their prefix, so they are kept
+        let prefix = default_prefix()?;
+        let sql = r#"
+            UPDATE `user` SET `phone_number` = SUBSTRING(`phone_number`, ?)
+            WHERE `phone_number` LIKE ?
+        "#;
+        let stmt = Statement::from_sql_and_values(
+            manager.get_database_backend(),
+            sql,
+            vec![
+                (prefix.len() as u32 + 1).into(),
+                format!("{}%", prefix).into(),
+            ],
+        );
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
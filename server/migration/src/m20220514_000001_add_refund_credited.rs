@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20220514_000001_add_refund_credited"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Refund::Table)
+                    .add_column(
+                        ColumnDef::new(Refund::Credited)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // refunds used to count as credit when they were made after the purchase was paid
+        let sql = r#"
+            UPDATE `refund` SET `credited` = TRUE
+            WHERE EXISTS (
+                SELECT 1 FROM `purchase`
+                WHERE `purchase`.`id` = `refund`.`purchase`
+                AND `refund`.`date` > `purchase`.`paid_date`
+            )
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Refund::Table)
+                    .drop_column(Refund::Credited)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Refund {
+    Table,
+    Credited,
+}
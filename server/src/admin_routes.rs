@@ -0,0 +1,186 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{self, ConnectInfo, Path},
+    Extension, Json,
+};
+use axum_extra::extract::CookieJar;
+use entity::{
+    password_reset, sea_orm,
+    user::{self, Entity as User},
+};
+use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, Set, TransactionTrait};
+
+use crate::{
+    dtos::{
+        AdminCreateUserDto, AdminResetPasswordDto, AdminUpdateUserDto, PasswordResetDto, UserDto,
+        UserRole,
+    },
+    errors::AppError,
+    invite_routes, user_routes, Config,
+};
+
+const PASSWORD_RESET_TOKEN_LENGTH: usize = 32;
+
+/// Makes sure the request comes from an admin, returning their user id.
+/// Requests from the admin subnet are trusted even without an admin account,
+/// otherwise there would be no way to appoint the first admin.
+/// The ip must be the socket address, headers like X-Forwarded-For can be forged.
+pub(crate) async fn require_admin(
+    ip: IpAddr,
+    jar: &CookieJar,
+    config: &Config,
+    conn: &DatabaseConnection,
+) -> Result<Option<u32>, AppError> {
+    let user_id = match crate::jwt_helpers::get_login(jar, &config.hmac_secret) {
+        Ok(user_id) => user_id,
+        Err(_) if config.admin_subnet.contains(ip) => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    let user = crate::jwt_helpers::enabled_user(user_id, conn).await?;
+    if UserRole::from(user.role.as_str()) != UserRole::Admin && !config.admin_subnet.contains(ip) {
+        return Err(AppError::Forbidden);
+    }
+    Ok(Some(user_id))
+}
+
+pub(crate) async fn list_users(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<Vec<UserDto>>, AppError> {
+    require_admin(addr.ip(), &jar, config, conn).await?;
+
+    let users = User::find()
+        .order_by_asc(user::Column::Name)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(UserDto::from_entity)
+        .collect::<Result<_, _>>()?;
+    Ok(Json(users))
+}
+
+/// Creates an account directly, without an invite
+pub(crate) async fn create_user(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    extract::Json(user_dto): extract::Json<AdminCreateUserDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<UserDto>, AppError> {
+    require_admin(addr.ip(), &jar, config, conn).await?;
+
+    let name = user_routes::validate_name(&user_dto.name)?;
+    let phone_number =
+        user_routes::normalize_phone_number(&user_dto.phone_number, &config.default_country_code)?;
+    user_routes::validate_password(&user_dto.password)?;
+
+    let txn = conn.begin().await?;
+
+    user_routes::check_phone_number_unused(&phone_number, None, &txn).await?;
+
+    let user = user::ActiveModel {
+        name: Set(name),
+        phone_number: Set(phone_number),
+        hashed_password: Set(user_routes::hash_password(&user_dto.password)?),
+        role: Set(user_dto.role.as_str().to_string()),
+        ..Default::default()
+    };
+    let user = user.insert(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(UserDto::from_entity(user)?))
+}
+
+/// Renames a user, changes their role or disables their account
+pub(crate) async fn update_user(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(user_id): Path<u32>,
+    extract::Json(user_dto): extract::Json<AdminUpdateUserDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<UserDto>, AppError> {
+    let admin_id = require_admin(addr.ip(), &jar, config, conn).await?;
+
+    // an admin locking themselves out would need the admin subnet to undo it
+    if admin_id == Some(user_id) && (user_dto.disabled || user_dto.role != UserRole::Admin) {
+        return Err(AppError::BadInput("you can't disable or demote yourself"));
+    }
+
+    let name = user_routes::validate_name(&user_dto.name)?;
+
+    let user = User::find_by_id(user_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchUser)?;
+
+    let mut user: user::ActiveModel = user.into();
+    user.name = Set(name);
+    user.role = Set(user_dto.role.as_str().to_string());
+    user.disabled = Set(user_dto.disabled);
+    let user = user.update(conn).await?;
+
+    Ok(Json(UserDto::from_entity(user)?))
+}
+
+pub(crate) async fn reset_password(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(user_id): Path<u32>,
+    extract::Json(password_dto): extract::Json<AdminResetPasswordDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<UserDto>, AppError> {
+    require_admin(addr.ip(), &jar, config, conn).await?;
+
+    user_routes::validate_password(&password_dto.password)?;
+
+    let user = User::find_by_id(user_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchUser)?;
+
+    let mut user: user::ActiveModel = user.into();
+    user.hashed_password = Set(user_routes::hash_password(&password_dto.password)?);
+    let user = user.update(conn).await?;
+
+    Ok(Json(UserDto::from_entity(user)?))
+}
+
+/// Creates a one-time link for the user to choose a new password themselves
+pub(crate) async fn create_password_reset(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(user_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<PasswordResetDto>, AppError> {
+    let admin_id = require_admin(addr.ip(), &jar, config, conn).await?;
+
+    User::find_by_id(user_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchUser)?;
+
+    let token = invite_routes::generate_code(PASSWORD_RESET_TOKEN_LENGTH);
+    let now = chrono::offset::Utc::now();
+    let reset = password_reset::ActiveModel {
+        token_hash: Set(user_routes::hash_reset_token(&token)),
+        user: Set(user_id),
+        created_by: Set(admin_id),
+        created_date: Set(now),
+        expiry_date: Set(now + config.password_reset_validity),
+        ..Default::default()
+    };
+    let reset = reset.insert(conn).await?;
+
+    Ok(Json(PasswordResetDto {
+        token,
+        expiry_date: reset.expiry_date,
+    }))
+}
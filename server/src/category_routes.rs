@@ -0,0 +1,110 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{self, ConnectInfo, Path},
+    Extension, Json,
+};
+use axum_extra::extract::CookieJar;
+use entity::{
+    category::{self, Entity as Category},
+    sea_orm,
+};
+use sea_orm::{prelude::*, ConnectionTrait, DatabaseConnection, QueryOrder, Set};
+
+use crate::errors::AppError;
+use crate::{admin_routes, dtos::CategoryDto, Config};
+
+pub(crate) async fn list(
+    Extension(ref conn): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<CategoryDto>>, AppError> {
+    let categories = Category::find()
+        .order_by_asc(category::Column::Name)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(CategoryDto::from_entity)
+        .collect();
+    Ok(Json(categories))
+}
+
+/// Trims the name and makes sure no other category uses it
+async fn validate_category_name<C: ConnectionTrait>(
+    name: &str,
+    category_id: Option<u32>,
+    conn: &C,
+) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadInput("name can't be empty"));
+    }
+
+    let existing = Category::find()
+        .filter(category::Column::Name.eq(name))
+        .one(conn)
+        .await?;
+    match existing {
+        Some(existing) if Some(existing.id) != category_id => Err(AppError::DuplicateCategory),
+        _ => Ok(name.to_string()),
+    }
+}
+
+pub(crate) async fn insert(
+    extract::Json(category_dto): extract::Json<CategoryDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<CategoryDto>, AppError> {
+    crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn).await?;
+
+    let name = validate_category_name(&category_dto.name, None, conn).await?;
+    let category = category::ActiveModel {
+        name: Set(name),
+        ..Default::default()
+    };
+    let category = category.insert(conn).await?;
+
+    Ok(Json(CategoryDto::from_entity(category)))
+}
+
+/// Renaming and deleting categories affects everyone's products, so only admins can do it
+pub(crate) async fn update(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(category_id): Path<u32>,
+    extract::Json(category_dto): extract::Json<CategoryDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<CategoryDto>, AppError> {
+    admin_routes::require_admin(addr.ip(), &jar, config, conn).await?;
+
+    let category = Category::find_by_id(category_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchCategory)?;
+    let name = validate_category_name(&category_dto.name, Some(category.id), conn).await?;
+
+    let mut category: category::ActiveModel = category.into();
+    category.name = Set(name);
+    let category = category.update(conn).await?;
+
+    Ok(Json(CategoryDto::from_entity(category)))
+}
+
+pub(crate) async fn delete(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(category_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<(), AppError> {
+    admin_routes::require_admin(addr.ip(), &jar, config, conn).await?;
+
+    // products of the category become uncategorized
+    let category = Category::find_by_id(category_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchCategory)?;
+    category.delete(conn).await?;
+
+    Ok(())
+}
@@ -1,21 +1,43 @@
+use std::collections::HashMap;
+
+use entity::category;
+use entity::invite;
 use entity::product;
+use entity::product_price_history;
 use entity::purchase;
+use entity::refund;
+use entity::settlement;
 use entity::user;
 use sea_orm::prelude::*;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
 use serde::{Deserialize, Serialize};
 
 use crate::errors::AppError;
+use crate::image_helpers;
 
 #[derive(Serialize, Deserialize)]
 pub struct ProductDto {
     pub(crate) id: Option<u32>,
     pub(crate) seller_id: Option<u32>,
     pub(crate) seller_name: Option<String>,
+    /// Only read when inserting, updates go through restock and purchases
+    #[serde(default)]
     pub(crate) stock: u32,
     pub(crate) price: u32,
     pub(crate) name: String,
     pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) archived: bool,
+    #[serde(default)]
+    pub(crate) category_id: Option<u32>,
+    #[serde(default)]
+    pub(crate) barcode: Option<String>,
+    #[serde(skip_deserializing)]
+    pub(crate) image_url: Option<String>,
+    #[serde(skip_deserializing)]
+    pub(crate) thumbnail_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub(crate) price_change_reason: Option<String>,
 }
 
 impl ProductDto {
@@ -27,7 +49,13 @@ impl ProductDto {
             .one(conn)
             .await?
             .expect("seller of product must exist");
-        Ok(Self {
+        Ok(Self::from_entity_with_seller(entity, seller))
+    }
+
+    /// Builds the DTO from a product that was queried together with its seller,
+    /// e.g. with `find_also_related(user::Entity)`
+    pub(crate) fn from_entity_with_seller(entity: product::Model, seller: user::Model) -> Self {
+        Self {
             id: Some(entity.id),
             seller_id: Some(entity.seller),
             seller_name: Some(seller.name),
@@ -35,10 +63,152 @@ impl ProductDto {
             price: entity.price,
             name: entity.name,
             description: entity.description,
+            archived: entity.archived,
+            category_id: entity.category,
+            barcode: entity.barcode,
+            image_url: entity.image.as_deref().map(image_helpers::image_url),
+            thumbnail_url: entity
+                .image
+                .as_deref()
+                .map(|image| image_helpers::image_url(&image_helpers::thumbnail_name(image))),
+            price_change_reason: None,
+        }
+    }
+
+    /// Builds the DTOs from the result of a product query joined with the sellers
+    pub(crate) fn from_joined(rows: Vec<(product::Model, Option<user::Model>)>) -> Vec<Self> {
+        rows.into_iter()
+            .map(|(product, seller)| {
+                Self::from_entity_with_seller(
+                    product,
+                    seller.expect("seller of product must exist"),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Looks up all the given users with a single query, keyed by id
+async fn find_users<C: ConnectionTrait>(
+    user_ids: Vec<u32>,
+    conn: &C,
+) -> Result<HashMap<u32, user::Model>, AppError> {
+    if user_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(user::Entity::find()
+        .filter(user::Column::Id.is_in(user_ids))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect())
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProductSort {
+    #[default]
+    Stock,
+    Price,
+    Name,
+    Newest,
+    Popularity,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ProductListQueryDto {
+    /// Text to look for in the name or description
+    pub(crate) search: Option<String>,
+    pub(crate) seller: Option<u32>,
+    pub(crate) category: Option<u32>,
+    #[serde(default)]
+    pub(crate) sort: ProductSort,
+    #[serde(default)]
+    pub(crate) include_out_of_stock: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CategoryDto {
+    pub(crate) id: Option<u32>,
+    pub(crate) name: String,
+}
+
+impl CategoryDto {
+    pub(crate) fn from_entity(entity: category::Model) -> Self {
+        Self {
+            id: Some(entity.id),
+            name: entity.name,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct PriceHistoryDto {
+    pub(crate) old_price: u32,
+    pub(crate) new_price: u32,
+    pub(crate) date: DateTimeUtc,
+    pub(crate) actor_name: String,
+    pub(crate) reason: Option<String>,
+}
+
+impl PriceHistoryDto {
+    pub(crate) fn from_entity(
+        entity: product_price_history::Model,
+        actor: user::Model,
+    ) -> Result<Self, AppError> {
+        Ok(Self {
+            old_price: entity.old_price,
+            new_price: entity.new_price,
+            date: entity.date,
+            actor_name: actor.name,
+            reason: entity.reason,
         })
     }
 }
 
+#[derive(Deserialize)]
+pub(crate) struct RestockDto {
+    pub(crate) quantity: u32,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AdjustStockDto {
+    /// Items to add, or to remove when negative
+    pub(crate) delta: i64,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CheckoutItemDto {
+    pub(crate) product_id: u32,
+    pub(crate) quantity: u32,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CheckoutDto {
+    pub(crate) items: Vec<CheckoutItemDto>,
+}
+
+/// Body of every error response
+#[derive(Serialize)]
+pub(crate) struct ErrorDto {
+    /// Stable, machine-readable name of the error
+    pub(crate) code: &'static str,
+    pub(crate) message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) details: Option<serde_json::Value>,
+}
+
+/// Why one line of a checkout can't be bought
+#[derive(Debug, Serialize)]
+pub(crate) struct CheckoutErrorDto {
+    pub(crate) product_id: u32,
+    pub(crate) message: &'static str,
+    /// Stock that is left, when there isn't enough for the line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) stock: Option<u32>,
+}
+
 #[derive(Deserialize)]
 pub struct LoginDto {
     pub(crate) phone: String,
@@ -50,6 +220,13 @@ pub struct UserDto {
     pub(crate) id: u32,
     pub(crate) name: String,
     pub(crate) phone_number: String,
+    #[serde(default)]
+    pub(crate) role: UserRole,
+    #[serde(default)]
+    pub(crate) disabled: bool,
+    /// Total credit the user has with all sellers, only sent to the user themselves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) credit: Option<i64>,
 }
 
 impl UserDto {
@@ -58,15 +235,93 @@ impl UserDto {
             id: entity.id,
             name: entity.name,
             phone_number: entity.phone_number,
+            role: entity.role.as_str().into(),
+            disabled: entity.disabled,
+            credit: None,
         })
     }
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum UserRole {
+    #[default]
+    User,
+    /// Can manage every user account
+    Admin,
+}
+
+impl UserRole {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            UserRole::User => "user",
+            UserRole::Admin => "admin",
+        }
+    }
+}
+
+impl From<&str> for UserRole {
+    fn from(role: &str) -> Self {
+        match role {
+            "admin" => UserRole::Admin,
+            _ => UserRole::User,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AdminCreateUserDto {
+    pub(crate) name: String,
+    pub(crate) phone_number: String,
+    pub(crate) password: String,
+    #[serde(default)]
+    pub(crate) role: UserRole,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AdminUpdateUserDto {
+    pub(crate) name: String,
+    pub(crate) role: UserRole,
+    pub(crate) disabled: bool,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AdminResetPasswordDto {
+    pub(crate) password: String,
+}
+
+/// One-time link for a user to choose a new password, the token is only shown once
+#[derive(Serialize)]
+pub(crate) struct PasswordResetDto {
+    pub(crate) token: String,
+    pub(crate) expiry_date: DateTimeUtc,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ResetPasswordDto {
+    pub(crate) token: String,
+    pub(crate) password: String,
+}
+
+/// Fields of their own profile a user wants to change, missing fields are left as they are
+#[derive(Deserialize)]
+pub(crate) struct UpdateUserInfoDto {
+    pub(crate) name: Option<String>,
+    pub(crate) phone_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ChangePasswordDto {
+    pub(crate) old_password: String,
+    pub(crate) new_password: String,
+}
+
 #[derive(Deserialize)]
 pub struct RegisterDto {
     pub(crate) name: String,
     pub(crate) phone_number: String,
     pub(crate) password: String,
+    pub(crate) invite_code: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -78,6 +333,8 @@ pub struct PurchaseDto {
     pub(crate) unit_price: Option<u32>,
     pub(crate) date: Option<DateTimeUtc>,
     pub(crate) paid_date: Option<DateTimeUtc>,
+    #[serde(default)]
+    pub(crate) refunds: Vec<RefundDto>,
 }
 
 impl PurchaseDto {
@@ -85,34 +342,333 @@ impl PurchaseDto {
         entity: purchase::Model,
         conn: &DatabaseConnection,
     ) -> Result<Self, AppError> {
-        let product = product::Entity::find_by_id(entity.product)
-            .one(conn)
+        Ok(Self::from_entities(vec![entity], conn)
             .await?
-            .expect("product of purchase must exist");
-        let buyer = user::Entity::find_by_id(entity.buyer)
-            .one(conn)
+            .pop()
+            .expect("one DTO is built for each entity"))
+    }
+
+    /// Builds the DTOs of a whole listing, loading the products, sellers, buyers and refunds
+    /// of all purchases at once so the number of queries doesn't grow with the listing
+    pub(crate) async fn from_entities(
+        entities: Vec<purchase::Model>,
+        conn: &DatabaseConnection,
+    ) -> Result<Vec<Self>, AppError> {
+        if entities.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let product_ids: Vec<u32> = entities.iter().map(|entity| entity.product).collect();
+        let buyer_ids: Vec<u32> = entities.iter().map(|entity| entity.buyer).collect();
+        let purchase_ids: Vec<u32> = entities.iter().map(|entity| entity.id).collect();
+
+        let products: HashMap<u32, (product::Model, Option<user::Model>)> = product::Entity::find()
+            .filter(product::Column::Id.is_in(product_ids))
+            .find_also_related(user::Entity)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|(product, seller)| (product.id, (product, seller)))
+            .collect();
+        let buyers = find_users(buyer_ids, conn).await?;
+        let mut refunds: HashMap<u32, Vec<RefundDto>> = HashMap::new();
+        for refund in refund::Entity::find()
+            .filter(refund::Column::Purchase.is_in(purchase_ids))
+            .all(conn)
             .await?
-            .expect("buyer of purchase must exist");
+        {
+            refunds
+                .entry(refund.purchase)
+                .or_default()
+                .push(RefundDto::from_entity(refund)?);
+        }
+
+        entities
+            .into_iter()
+            .map(|entity| {
+                let (product, seller) = products
+                    .get(&entity.product)
+                    .cloned()
+                    .expect("product of purchase must exist");
+                let buyer = buyers
+                    .get(&entity.buyer)
+                    .cloned()
+                    .expect("buyer of purchase must exist");
+                Ok(Self {
+                    id: Some(entity.id),
+                    buyer: Some(UserDto::from_entity(buyer)?),
+                    product: Some(ProductDto::from_entity_with_seller(
+                        product,
+                        seller.expect("seller of product must exist"),
+                    )),
+                    quantity: entity.quantity,
+                    unit_price: Some(entity.unit_price),
+                    date: Some(entity.date),
+                    paid_date: entity.paid_date,
+                    refunds: refunds.remove(&entity.id).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Total price of the purchase, minus everything that has been refunded
+    pub(crate) fn amount_due(&self) -> u32 {
+        let refunded: u32 = self.refunds.iter().map(|refund| refund.amount).sum();
+        (self.quantity * self.unit_price.expect("purchase must have unit price"))
+            .saturating_sub(refunded)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PurchaseHistoryQueryDto {
+    pub(crate) before_date: Option<DateTimeUtc>,
+    pub(crate) before_id: Option<u32>,
+    pub(crate) limit: Option<u64>,
+    pub(crate) from: Option<DateTimeUtc>,
+    pub(crate) to: Option<DateTimeUtc>,
+    pub(crate) product: Option<u32>,
+    pub(crate) seller: Option<u32>,
+    pub(crate) paid: Option<bool>,
+}
+
+/// Position of the last purchase of a page, passed back to get the next one
+#[derive(Serialize)]
+pub(crate) struct PurchaseCursorDto {
+    pub(crate) before_date: DateTimeUtc,
+    pub(crate) before_id: u32,
+}
+
+#[derive(Serialize)]
+pub(crate) struct PurchaseHistoryPageDto {
+    pub(crate) purchases: Vec<PurchaseDto>,
+    pub(crate) next_cursor: Option<PurchaseCursorDto>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RefundDto {
+    pub(crate) id: u32,
+    pub(crate) amount: u32,
+    pub(crate) reason: String,
+    pub(crate) returned_quantity: u32,
+    pub(crate) date: DateTimeUtc,
+}
+
+impl RefundDto {
+    pub(crate) fn from_entity(entity: refund::Model) -> Result<Self, AppError> {
         Ok(Self {
-            id: Some(entity.id),
-            buyer: Some(UserDto::from_entity(buyer)?),
-            product: Some(ProductDto::from_entity(product, conn).await?),
-            quantity: entity.quantity,
-            unit_price: Some(entity.unit_price),
-            date: Some(entity.date),
-            paid_date: entity.paid_date,
+            id: entity.id,
+            amount: entity.amount,
+            reason: entity.reason,
+            returned_quantity: entity.returned_quantity,
+            date: entity.date,
         })
     }
 }
 
+#[derive(Deserialize)]
+pub(crate) struct RefundPurchaseDto {
+    pub(crate) amount: u32,
+    pub(crate) reason: String,
+    pub(crate) returned_quantity: Option<u32>,
+}
+
 #[derive(Serialize)]
 pub(crate) struct BuyerGroupedPurchasesDto {
     pub(crate) buyer: UserDto,
     pub(crate) amount_due: u32,
+    pub(crate) credit: i64,
+    pub(crate) purchases: Vec<PurchaseDto>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SellerGroupedPurchasesDto {
+    pub(crate) seller: UserDto,
+    pub(crate) amount_due: u32,
+    pub(crate) credit: i64,
     pub(crate) purchases: Vec<PurchaseDto>,
 }
 
 #[derive(Deserialize)]
 pub(crate) struct PayPurchaseUserBulkDto {
     pub(crate) count: u64,
+    #[serde(default)]
+    pub(crate) payment_method: PaymentMethod,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PaymentMethod {
+    #[default]
+    Cash,
+    BankTransfer,
+    MbWay,
+    Other,
+    /// Paid from the buyer's credit with the seller
+    Credit,
+}
+
+impl PaymentMethod {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            PaymentMethod::Cash => "cash",
+            PaymentMethod::BankTransfer => "bank_transfer",
+            PaymentMethod::MbWay => "mb_way",
+            PaymentMethod::Other => "other",
+            PaymentMethod::Credit => "credit",
+        }
+    }
+}
+
+impl From<&str> for PaymentMethod {
+    fn from(method: &str) -> Self {
+        match method {
+            "cash" => PaymentMethod::Cash,
+            "bank_transfer" => PaymentMethod::BankTransfer,
+            "mb_way" => PaymentMethod::MbWay,
+            "credit" => PaymentMethod::Credit,
+            _ => PaymentMethod::Other,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PayPurchaseDto {
+    #[serde(default)]
+    pub(crate) payment_method: PaymentMethod,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RecordPaymentDto {
+    pub(crate) amount: u32,
+    #[serde(default)]
+    pub(crate) payment_method: PaymentMethod,
+}
+
+#[derive(Serialize)]
+pub(crate) struct WalletDto {
+    pub(crate) seller: UserDto,
+    pub(crate) buyer: UserDto,
+    pub(crate) balance: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SettlementDto {
+    pub(crate) id: u32,
+    pub(crate) seller: UserDto,
+    pub(crate) buyer: UserDto,
+    pub(crate) amount: u32,
+    pub(crate) payment_method: PaymentMethod,
+    pub(crate) credit_used: u32,
+    pub(crate) date: DateTimeUtc,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) purchases: Option<Vec<PurchaseDto>>,
+}
+
+impl SettlementDto {
+    pub(crate) async fn from_entity(
+        entity: settlement::Model,
+        conn: &DatabaseConnection,
+    ) -> Result<Self, AppError> {
+        Ok(Self::from_entities(vec![entity], conn)
+            .await?
+            .pop()
+            .expect("one DTO is built for each entity"))
+    }
+
+    /// Builds the DTOs of a whole listing, loading all sellers and buyers at once
+    pub(crate) async fn from_entities(
+        entities: Vec<settlement::Model>,
+        conn: &DatabaseConnection,
+    ) -> Result<Vec<Self>, AppError> {
+        if entities.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let users = find_users(
+            entities
+                .iter()
+                .flat_map(|entity| [entity.seller, entity.buyer])
+                .collect(),
+            conn,
+        )
+        .await?;
+
+        entities
+            .into_iter()
+            .map(|entity| {
+                let seller = users
+                    .get(&entity.seller)
+                    .cloned()
+                    .expect("seller of settlement must exist");
+                let buyer = users
+                    .get(&entity.buyer)
+                    .cloned()
+                    .expect("buyer of settlement must exist");
+                Ok(Self {
+                    id: entity.id,
+                    seller: UserDto::from_entity(seller)?,
+                    buyer: UserDto::from_entity(buyer)?,
+                    amount: entity.amount,
+                    payment_method: entity.payment_method.as_str().into(),
+                    credit_used: entity.credit_used,
+                    date: entity.date,
+                    purchases: None,
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) async fn from_entity_with_purchases(
+        entity: settlement::Model,
+        conn: &DatabaseConnection,
+    ) -> Result<Self, AppError> {
+        let purchases = entity.find_related(purchase::Entity).all(conn).await?;
+        let mut dto = Self::from_entity(entity, conn).await?;
+        dto.purchases = Some(PurchaseDto::from_entities(purchases, conn).await?);
+        Ok(dto)
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct InviteDto {
+    pub(crate) code: String,
+    pub(crate) created_date: DateTimeUtc,
+    pub(crate) expiry_date: DateTimeUtc,
+    pub(crate) used_by: Option<UserDto>,
+    pub(crate) used_date: Option<DateTimeUtc>,
+}
+
+impl InviteDto {
+    /// Builds the DTOs of a whole listing, loading all the users that registered with them at once
+    pub(crate) async fn from_entities(
+        entities: Vec<invite::Model>,
+        conn: &DatabaseConnection,
+    ) -> Result<Vec<Self>, AppError> {
+        let users = find_users(
+            entities
+                .iter()
+                .filter_map(|entity| entity.used_by)
+                .collect(),
+            conn,
+        )
+        .await?;
+
+        entities
+            .into_iter()
+            .map(|entity| {
+                let used_by = entity
+                    .used_by
+                    .and_then(|user_id| users.get(&user_id).cloned())
+                    .map(UserDto::from_entity)
+                    .transpose()?;
+                Ok(Self {
+                    code: entity.code,
+                    created_date: entity.created_date,
+                    expiry_date: entity.expiry_date,
+                    used_by,
+                    used_date: entity.used_date,
+                })
+            })
+            .collect()
+    }
 }
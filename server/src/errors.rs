@@ -1,56 +1,202 @@
+use std::borrow::Cow;
+
 use argon2::password_hash::errors::Error as PwHashError;
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
 use sea_orm::error::DbErr;
+use serde_json::json;
+
+use crate::dtos::{CheckoutErrorDto, ErrorDto};
 
 pub(crate) enum AppError {
     BadInput(&'static str),
     NoSuchUser,
     LoginError,
     DuplicateUser,
+    UserDisabled,
+    InvalidInvite,
+    InvalidResetToken,
     NoSuchProduct,
+    ProductArchived,
     NoSuchPurchase,
+    NoSuchSettlement,
+    NoSuchCategory,
+    DuplicateCategory,
+    DuplicateBarcode,
     NotEnoughStock,
+    /// Quantity of a purchase is zero or above the maximum per purchase
+    InvalidQuantity {
+        max: u32,
+    },
+    /// Lines of a checkout that can't be bought, sent back so each one can be fixed
+    CheckoutFailed(Vec<CheckoutErrorDto>),
     PurchaseAlreadyPaid,
+    PurchaseNotPaid,
+    UndoWindowExpired,
     BulkCountMismatch,
     Unauthorized,
     Forbidden,
     JwtError(jwt::error::Error),
     PwhError(PwHashError),
     DbError(DbErr),
+    IoError(std::io::Error),
+    ImageError(image::ImageError),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::BadInput(message) => (StatusCode::BAD_REQUEST, message),
-            AppError::NoSuchUser => (StatusCode::BAD_REQUEST, "no such user"),
-            AppError::DuplicateUser => (StatusCode::CONFLICT, "that user already exists"),
-            AppError::NoSuchProduct => (StatusCode::NOT_FOUND, "no such product"),
-            AppError::NoSuchPurchase => (StatusCode::NOT_FOUND, "no such purchase"),
-            AppError::NotEnoughStock => (StatusCode::CONFLICT, "not enough stock"),
-            AppError::PurchaseAlreadyPaid => {
-                (StatusCode::CONFLICT, "purchase has already been paid")
+        let details = match &self {
+            AppError::InvalidQuantity { max } => Some(json!({ "max": max })),
+            AppError::CheckoutFailed(lines) => serde_json::to_value(lines).ok(),
+            _ => None,
+        };
+
+        // the client only sees a generic message, keep the cause for whoever runs the server
+        match &self {
+            AppError::PwhError(error) if *error != PwHashError::Password => {
+                log::error!("password hashing error: {}", error)
             }
+            AppError::JwtError(error) => log::error!("jwt error: {}", error),
+            AppError::DbError(error) => log::error!("database error: {}", error),
+            AppError::IoError(error) => log::error!("io error: {}", error),
+            AppError::ImageError(error) => log::error!("image error: {}", error),
+            _ => {}
+        }
+
+        let (status, code, message): (StatusCode, &str, Cow<str>) = match self {
+            AppError::BadInput(message) => (StatusCode::BAD_REQUEST, "bad_input", message.into()),
+            AppError::NoSuchUser => (
+                StatusCode::BAD_REQUEST,
+                "no_such_user",
+                "no such user".into(),
+            ),
+            AppError::DuplicateUser => (
+                StatusCode::CONFLICT,
+                "duplicate_user",
+                "that user already exists".into(),
+            ),
+            AppError::UserDisabled => (
+                StatusCode::FORBIDDEN,
+                "user_disabled",
+                "this account has been disabled".into(),
+            ),
+            AppError::InvalidInvite => (
+                StatusCode::BAD_REQUEST,
+                "invalid_invite",
+                "invite code is invalid, used or expired".into(),
+            ),
+            AppError::InvalidResetToken => (
+                StatusCode::BAD_REQUEST,
+                "invalid_reset_token",
+                "password reset link is invalid, used or expired".into(),
+            ),
+            AppError::NoSuchProduct => (
+                StatusCode::NOT_FOUND,
+                "no_such_product",
+                "no such product".into(),
+            ),
+            AppError::ProductArchived => (
+                StatusCode::CONFLICT,
+                "product_archived",
+                "product has been archived".into(),
+            ),
+            AppError::NoSuchPurchase => (
+                StatusCode::NOT_FOUND,
+                "no_such_purchase",
+                "no such purchase".into(),
+            ),
+            AppError::NoSuchSettlement => (
+                StatusCode::NOT_FOUND,
+                "no_such_settlement",
+                "no such settlement".into(),
+            ),
+            AppError::NoSuchCategory => (
+                StatusCode::NOT_FOUND,
+                "no_such_category",
+                "no such category".into(),
+            ),
+            AppError::DuplicateCategory => (
+                StatusCode::CONFLICT,
+                "duplicate_category",
+                "that category already exists".into(),
+            ),
+            AppError::DuplicateBarcode => (
+                StatusCode::CONFLICT,
+                "duplicate_barcode",
+                "a product with that barcode already exists".into(),
+            ),
+            AppError::NotEnoughStock => (
+                StatusCode::CONFLICT,
+                "not_enough_stock",
+                "not enough stock".into(),
+            ),
+            AppError::InvalidQuantity { max } => (
+                StatusCode::BAD_REQUEST,
+                "invalid_quantity",
+                format!("quantity must be between 1 and {}", max).into(),
+            ),
+            AppError::CheckoutFailed(_) => (
+                StatusCode::CONFLICT,
+                "checkout_failed",
+                "some items can't be bought".into(),
+            ),
+            AppError::PurchaseAlreadyPaid => (
+                StatusCode::CONFLICT,
+                "purchase_already_paid",
+                "purchase has already been paid".into(),
+            ),
+            AppError::PurchaseNotPaid => (
+                StatusCode::CONFLICT,
+                "purchase_not_paid",
+                "purchase has not been paid".into(),
+            ),
+            AppError::UndoWindowExpired => (
+                StatusCode::CONFLICT,
+                "undo_window_expired",
+                "purchase can no longer be cancelled".into(),
+            ),
             AppError::BulkCountMismatch => (
                 StatusCode::CONFLICT,
-                "affected count is different than expected",
+                "bulk_count_mismatch",
+                "affected count is different than expected".into(),
+            ),
+            AppError::PwhError(PwHashError::Password) | AppError::LoginError => (
+                StatusCode::UNAUTHORIZED,
+                "wrong_password",
+                "wrong password".into(),
+            ),
+            AppError::PwhError(_)
+            | AppError::DbError(_)
+            | AppError::JwtError(_)
+            | AppError::IoError(_)
+            | AppError::ImageError(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+                "internal servor error".into(),
             ),
-            AppError::PwhError(PwHashError::Password) | AppError::LoginError => {
-                (StatusCode::UNAUTHORIZED, "wrong password")
-            }
-            AppError::PwhError(_) | AppError::DbError(_) | AppError::JwtError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, "internal servor error")
-            }
             // TODO: how to clear jar **and** return StatusCode?
             // maybe UNAUTHORIZED redirects to login page?
-            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "login required"),
-            AppError::Forbidden => (StatusCode::FORBIDDEN, "not allowed to access this"),
+            AppError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "login required".into(),
+            ),
+            AppError::Forbidden => (
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "not allowed to access this".into(),
+            ),
         };
 
-        (status, error_message).into_response()
+        let body = ErrorDto {
+            code,
+            message: message.into_owned(),
+            details,
+        };
+        (status, Json(body)).into_response()
     }
 }
 
@@ -71,3 +217,15 @@ impl From<jwt::error::Error> for AppError {
         Self::JwtError(inner)
     }
 }
+
+impl From<std::io::Error> for AppError {
+    fn from(inner: std::io::Error) -> Self {
+        Self::IoError(inner)
+    }
+}
+
+impl From<image::ImageError> for AppError {
+    fn from(inner: image::ImageError) -> Self {
+        Self::ImageError(inner)
+    }
+}
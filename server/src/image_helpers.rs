@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use image::ImageFormat;
+
+use crate::errors::AppError;
+
+/// Largest image that can be uploaded, in bytes
+pub(crate) const MAX_IMAGE_SIZE: u64 = 5 * 1024 * 1024;
+/// Bounding box of the generated thumbnails, in pixels
+const THUMBNAIL_SIZE: u32 = 256;
+/// Path the upload directory is served under
+pub(crate) const UPLOADS_PATH: &str = "/uploads";
+
+/// Image format and file extension for the given content type
+pub(crate) fn image_format(content_type: &str) -> Result<(ImageFormat, &'static str), AppError> {
+    match content_type {
+        "image/png" => Ok((ImageFormat::Png, "png")),
+        "image/jpeg" => Ok((ImageFormat::Jpeg, "jpg")),
+        _ => Err(AppError::BadInput("image must be a PNG or JPEG file")),
+    }
+}
+
+pub(crate) fn thumbnail_name(image_name: &str) -> String {
+    match image_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}-thumb.{}", stem, extension),
+        None => format!("{}-thumb", image_name),
+    }
+}
+
+pub(crate) fn image_url(image_name: &str) -> String {
+    format!("{}/{}", UPLOADS_PATH, image_name)
+}
+
+/// Checks that the data is a valid image and stores it along with its thumbnail.
+/// This is CPU heavy, so it should run on a blocking thread.
+pub(crate) fn save_image(
+    data: &[u8],
+    format: ImageFormat,
+    upload_dir: &Path,
+    image_name: &str,
+) -> Result<(), AppError> {
+    let image = image::load_from_memory_with_format(data, format)
+        .map_err(|_| AppError::BadInput("file is not a valid image"))?;
+
+    let mut thumbnail = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut std::io::Cursor::new(&mut thumbnail), format)?;
+
+    std::fs::write(upload_dir.join(image_name), data)?;
+    std::fs::write(upload_dir.join(thumbnail_name(image_name)), thumbnail)?;
+    Ok(())
+}
+
+/// Removes an image and its thumbnail, ignoring files that are already gone
+pub(crate) async fn remove_image(upload_dir: &Path, image_name: &str) {
+    for name in [image_name.to_string(), thumbnail_name(image_name)] {
+        if let Err(err) = tokio::fs::remove_file(upload_dir.join(&name)).await {
+            log::warn!("failed to remove image {}: {}", name, err);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::net::SocketAddr;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{extract::ConnectInfo, Extension, Json};
+use axum_extra::extract::CookieJar;
+use entity::{
+    invite::{self, Entity as Invite},
+    sea_orm,
+};
+use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, Set};
+
+use crate::{dtos::InviteDto, errors::AppError, Config};
+
+/// Letters and digits that can't be mistaken for one another when read aloud or typed
+const INVITE_CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKMNPQRSTVWXYZ0123456789";
+const INVITE_CODE_LENGTH: usize = 16;
+
+pub(crate) async fn list(
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<Vec<InviteDto>>, AppError> {
+    let user_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let invites = Invite::find()
+        .filter(invite::Column::CreatedBy.eq(user_id))
+        .order_by_desc(invite::Column::CreatedDate)
+        .all(conn)
+        .await?;
+
+    Ok(Json(InviteDto::from_entities(invites, conn).await?))
+}
+
+/// Creates a single-use invite code.
+/// Any user can invite someone, and the admin subnet can create the very first invites.
+/// The subnet is matched against the socket address, headers like X-Forwarded-For can be forged.
+pub(crate) async fn create(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<InviteDto>, AppError> {
+    let created_by = match crate::jwt_helpers::get_login(&jar, &config.hmac_secret) {
+        Ok(user_id) => Some(crate::jwt_helpers::enabled_user(user_id, conn).await?.id),
+        Err(_) if config.admin_subnet.contains(addr.ip()) => None,
+        Err(error) => return Err(error),
+    };
+
+    let now = chrono::offset::Utc::now();
+    let invite = invite::ActiveModel {
+        code: Set(generate_code(INVITE_CODE_LENGTH)),
+        created_by: Set(created_by),
+        created_date: Set(now),
+        expiry_date: Set(now + config.invite_validity),
+        ..Default::default()
+    };
+    let invite = invite.insert(conn).await?;
+
+    Ok(Json(
+        InviteDto::from_entities(vec![invite], conn)
+            .await?
+            .pop()
+            .expect("one DTO is built for each entity"),
+    ))
+}
+
+/// Generates a random code that is easy to read out, also used for password reset tokens
+pub(crate) fn generate_code(length: usize) -> String {
+    let mut bytes = vec![0u8; length];
+    OsRng.fill_bytes(&mut bytes);
+    // 256 is a multiple of the alphabet length, so every character is equally likely
+    bytes
+        .iter()
+        .map(|byte| {
+            char::from(INVITE_CODE_ALPHABET[usize::from(*byte) % INVITE_CODE_ALPHABET.len()])
+        })
+        .collect()
+}
@@ -1,7 +1,9 @@
 use axum_extra::extract::{cookie::Cookie, CookieJar};
+use entity::{sea_orm, user};
 use hmac::{Hmac, Mac};
 use jwt::SignWithKey;
 use jwt::VerifyWithKey;
+use sea_orm::{prelude::*, ConnectionTrait};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 
@@ -35,3 +37,28 @@ pub(crate) fn get_login(jar: &CookieJar, hmac_secret: &[u8]) -> Result<u32, AppE
         Err(AppError::Unauthorized)
     }
 }
+
+/// Like `get_login`, but also makes sure the account still exists and hasn't been disabled
+/// since the token was issued
+pub(crate) async fn get_login_user<C: ConnectionTrait>(
+    jar: &CookieJar,
+    hmac_secret: &[u8],
+    conn: &C,
+) -> Result<user::Model, AppError> {
+    let user_id = get_login(jar, hmac_secret)?;
+    enabled_user(user_id, conn).await
+}
+
+pub(crate) async fn enabled_user<C: ConnectionTrait>(
+    user_id: u32,
+    conn: &C,
+) -> Result<user::Model, AppError> {
+    let user = user::Entity::find_by_id(user_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    if user.disabled {
+        return Err(AppError::UserDisabled);
+    }
+    Ok(user)
+}
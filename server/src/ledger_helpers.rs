@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use entity::{
+    payment, product,
+    purchase::{self, Entity as Purchase},
+    refund, sea_orm, settlement, user,
+};
+use migration::{Expr, Query};
+use sea_orm::{
+    prelude::*, ConnectionTrait, FromQueryResult, JoinType, QueryOrder, QuerySelect, Select, Set,
+};
+
+use crate::dtos::PaymentMethod;
+use crate::errors::AppError;
+
+/// Unpaid purchases a buyer has made from a seller, oldest first
+pub(crate) fn unpaid_purchases(buyer_id: u32, seller_id: u32) -> Select<Purchase> {
+    Purchase::find()
+        .filter(purchase::Column::Buyer.eq(buyer_id))
+        .filter(
+            purchase::Column::Product.in_subquery(
+                Query::select()
+                    .expr(Expr::col(product::Column::Id))
+                    .from(product::Entity)
+                    .and_where(Expr::col(product::Column::Seller).eq(seller_id))
+                    .to_owned(),
+            ),
+        )
+        .filter(purchase::Column::PaidDate.is_null())
+        .order_by_asc(purchase::Column::Date)
+        .order_by_asc(purchase::Column::Id)
+}
+
+/// Price of each purchase after refunds, keyed by purchase id.
+/// The refunds are read with a locking read, so a transaction sees the latest ones.
+pub(crate) async fn net_amounts<C: ConnectionTrait>(
+    purchases: &[purchase::Model],
+    conn: &C,
+) -> Result<HashMap<u32, u32>, AppError> {
+    let mut amounts: HashMap<u32, u32> = purchases
+        .iter()
+        .map(|purchase| (purchase.id, purchase.quantity * purchase.unit_price))
+        .collect();
+    let refunds = refund::Entity::find()
+        .filter(refund::Column::Purchase.is_in(amounts.keys().copied().collect::<Vec<_>>()))
+        .lock_exclusive()
+        .all(conn)
+        .await?;
+    for refund in refunds {
+        if let Some(amount) = amounts.get_mut(&refund.purchase) {
+            *amount = amount.saturating_sub(refund.amount);
+        }
+    }
+    Ok(amounts)
+}
+
+#[derive(FromQueryResult)]
+struct RefundCredit {
+    buyer: u32,
+    seller: u32,
+    amount: u32,
+}
+
+/// Refunds made after their purchase was paid, the buyer gets those back as credit.
+/// Refunds made before that were already left out of the settlement.
+fn credited_refunds() -> Select<refund::Entity> {
+    refund::Entity::find()
+        .select_only()
+        .column(refund::Column::Amount)
+        .column_as(purchase::Column::Buyer, "buyer")
+        .column_as(product::Column::Seller, "seller")
+        .join(JoinType::InnerJoin, refund::Relation::Purchase.def())
+        .join(JoinType::InnerJoin, purchase::Relation::Product.def())
+        .filter(refund::Column::Credited.eq(true))
+}
+
+/// Credit left over from payments, keyed by (buyer, seller).
+/// Every payment and every refund of a paid purchase adds to the credit,
+/// every settlement takes away what it drew from it.
+/// A negative balance means the buyer owes the seller, e.g. after a settlement
+/// that used credit from a refund was reverted.
+async fn credit_balances<C: ConnectionTrait>(
+    payments: Select<payment::Entity>,
+    refunds: Select<refund::Entity>,
+    settlements: Select<settlement::Entity>,
+    conn: &C,
+) -> Result<HashMap<(u32, u32), i64>, AppError> {
+    let mut balances: HashMap<(u32, u32), i64> = HashMap::new();
+    for payment in payments.all(conn).await? {
+        *balances.entry((payment.buyer, payment.seller)).or_default() += i64::from(payment.amount);
+    }
+    for refund in refunds.into_model::<RefundCredit>().all(conn).await? {
+        *balances.entry((refund.buyer, refund.seller)).or_default() += i64::from(refund.amount);
+    }
+    for settlement in settlements
+        .filter(settlement::Column::CreditUsed.gt(0))
+        .all(conn)
+        .await?
+    {
+        *balances
+            .entry((settlement.buyer, settlement.seller))
+            .or_default() -= i64::from(settlement.credit_used);
+    }
+    Ok(balances
+        .into_iter()
+        .filter(|(_, balance)| *balance != 0)
+        .collect())
+}
+
+/// Credit each buyer has with the given seller, keyed by buyer id
+pub(crate) async fn seller_credit_balances<C: ConnectionTrait>(
+    seller_id: u32,
+    conn: &C,
+) -> Result<HashMap<u32, i64>, AppError> {
+    let balances = credit_balances(
+        payment::Entity::find().filter(payment::Column::Seller.eq(seller_id)),
+        credited_refunds().filter(product::Column::Seller.eq(seller_id)),
+        settlement::Entity::find().filter(settlement::Column::Seller.eq(seller_id)),
+        conn,
+    )
+    .await?;
+    Ok(balances
+        .into_iter()
+        .map(|((buyer, _), balance)| (buyer, balance))
+        .collect())
+}
+
+/// Credit the given buyer has with each seller, keyed by seller id
+pub(crate) async fn buyer_credit_balances<C: ConnectionTrait>(
+    buyer_id: u32,
+    conn: &C,
+) -> Result<HashMap<u32, i64>, AppError> {
+    let balances = credit_balances(
+        payment::Entity::find().filter(payment::Column::Buyer.eq(buyer_id)),
+        credited_refunds().filter(purchase::Column::Buyer.eq(buyer_id)),
+        settlement::Entity::find().filter(settlement::Column::Buyer.eq(buyer_id)),
+        conn,
+    )
+    .await?;
+    Ok(balances
+        .into_iter()
+        .map(|((_, seller), balance)| (seller, balance))
+        .collect())
+}
+
+/// Credit a buyer has with a seller
+pub(crate) async fn credit_balance<C: ConnectionTrait>(
+    buyer_id: u32,
+    seller_id: u32,
+    conn: &C,
+) -> Result<i64, AppError> {
+    pair_credit_balance(buyer_id, seller_id, false, conn).await
+}
+
+/// Credit a buyer has with a seller that can be spent right now.
+/// Locks the buyer's row until the transaction ends, so that concurrent requests
+/// can't spend the same credit twice. The ledger is read with locking reads, plain reads
+/// would see the transaction's snapshot, which can predate what the lock waited for.
+pub(crate) async fn spendable_credit<C: ConnectionTrait>(
+    buyer_id: u32,
+    seller_id: u32,
+    conn: &C,
+) -> Result<u32, AppError> {
+    lock_buyer(buyer_id, conn).await?;
+
+    let credit = pair_credit_balance(buyer_id, seller_id, true, conn).await?;
+    Ok(u32::try_from(credit.max(0)).unwrap_or(u32::MAX))
+}
+
+/// Locks the buyer's row until the transaction ends, which serializes everything that
+/// spends their credit. Transactions take this lock before any other: the shared lock an
+/// insert referencing the buyer takes would otherwise deadlock with the upgrade, and locking
+/// their purchases first would deadlock with a transaction that already holds the buyer.
+pub(crate) async fn lock_buyer<C: ConnectionTrait>(
+    buyer_id: u32,
+    conn: &C,
+) -> Result<(), AppError> {
+    user::Entity::find_by_id(buyer_id)
+        .lock_exclusive()
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchUser)?;
+    Ok(())
+}
+
+async fn pair_credit_balance<C: ConnectionTrait>(
+    buyer_id: u32,
+    seller_id: u32,
+    locking: bool,
+    conn: &C,
+) -> Result<i64, AppError> {
+    let mut payments = payment::Entity::find()
+        .filter(payment::Column::Buyer.eq(buyer_id))
+        .filter(payment::Column::Seller.eq(seller_id));
+    let mut refunds = credited_refunds()
+        .filter(purchase::Column::Buyer.eq(buyer_id))
+        .filter(product::Column::Seller.eq(seller_id));
+    let mut settlements = settlement::Entity::find()
+        .filter(settlement::Column::Buyer.eq(buyer_id))
+        .filter(settlement::Column::Seller.eq(seller_id));
+    if locking {
+        payments = payments.lock_exclusive();
+        refunds = refunds.lock_exclusive();
+        settlements = settlements.lock_exclusive();
+    }
+
+    let balances = credit_balances(payments, refunds, settlements, conn).await?;
+    Ok(balances
+        .get(&(buyer_id, seller_id))
+        .copied()
+        .unwrap_or_default())
+}
+
+/// Uses the buyer's credit to settle as many unpaid purchases as it covers, oldest first.
+/// Returns the settlement that was created, if any purchase could be paid.
+pub(crate) async fn apply_credit<C: ConnectionTrait>(
+    buyer_id: u32,
+    seller_id: u32,
+    payment_method: PaymentMethod,
+    conn: &C,
+) -> Result<Option<settlement::Model>, AppError> {
+    let mut credit = spendable_credit(buyer_id, seller_id, conn).await?;
+    if credit == 0 {
+        return Ok(None);
+    }
+
+    let purchases = unpaid_purchases(buyer_id, seller_id)
+        .lock_exclusive()
+        .all(conn)
+        .await?;
+    let amounts = net_amounts(&purchases, conn).await?;
+
+    let mut purchase_ids = Vec::new();
+    let mut amount = 0;
+    for purchase in purchases {
+        let purchase_amount = amounts[&purchase.id];
+        if purchase_amount <= credit {
+            credit -= purchase_amount;
+            amount += purchase_amount;
+            purchase_ids.push(purchase.id);
+        }
+    }
+    if purchase_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let now = chrono::offset::Utc::now();
+    let settlement = settlement::ActiveModel {
+        seller: Set(seller_id),
+        buyer: Set(buyer_id),
+        date: Set(now),
+        amount: Set(amount),
+        payment_method: Set(payment_method.as_str().to_string()),
+        credit_used: Set(amount),
+        ..Default::default()
+    };
+    let settlement = settlement.insert(conn).await?;
+
+    let purchase = purchase::ActiveModel {
+        paid_date: Set(Some(now)),
+        settlement: Set(Some(settlement.id)),
+        ..Default::default()
+    };
+    Purchase::update_many()
+        .set(purchase)
+        .filter(purchase::Column::Id.is_in(purchase_ids))
+        .exec(conn)
+        .await?;
+
+    Ok(Some(settlement))
+}
+
+/// Adds a payment from the buyer to the seller to the ledger and uses the resulting credit
+/// to settle what it can
+pub(crate) async fn record_payment<C: ConnectionTrait>(
+    buyer_id: u32,
+    seller_id: u32,
+    amount: u32,
+    payment_method: PaymentMethod,
+    conn: &C,
+) -> Result<Option<settlement::Model>, AppError> {
+    if amount == 0 {
+        return Err(AppError::BadInput("amount must be greater than 0"));
+    }
+
+    lock_buyer(buyer_id, conn).await?;
+
+    let payment = payment::ActiveModel {
+        buyer: Set(buyer_id),
+        seller: Set(seller_id),
+        amount: Set(amount),
+        payment_method: Set(payment_method.as_str().to_string()),
+        date: Set(chrono::offset::Utc::now()),
+        ..Default::default()
+    };
+    payment.insert(conn).await?;
+
+    apply_credit(buyer_id, seller_id, payment_method, conn).await
+}
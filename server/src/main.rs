@@ -1,7 +1,7 @@
 use axum::body::{boxed, Body};
 use axum::http::{Response, StatusCode};
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, get_service, post, put},
     Router,
 };
 use clap::Parser;
@@ -18,12 +18,20 @@ use tower_http::add_extension::AddExtensionLayer;
 use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
 
+mod admin_routes;
+mod category_routes;
 mod dtos;
 mod errors;
+mod image_helpers;
+mod invite_routes;
 mod jwt_helpers;
+mod ledger_helpers;
 mod product_routes;
 mod purchase_routes;
+mod settlement_routes;
+mod stock_helpers;
 mod user_routes;
+mod wallet_routes;
 
 // Setup the command line interface with clap.
 #[derive(Parser, Debug)]
@@ -44,12 +52,23 @@ struct Opt {
     /// set the directory where static files are to be found
     #[clap(long = "static-dir", default_value = "../dist")]
     static_dir: String,
+
+    /// set the directory where uploaded images are stored
+    #[clap(long = "upload-dir", default_value = "./uploads")]
+    upload_dir: String,
 }
 
 #[derive(Debug, Clone)]
 struct Config {
     hmac_secret: Box<[u8]>,
     admin_subnet: IpNetwork,
+    purchase_undo_window: chrono::Duration,
+    max_purchase_quantity: u32,
+    invite_validity: chrono::Duration,
+    password_reset_validity: chrono::Duration,
+    /// Country calling code of phone numbers typed without one, e.g. `351`
+    default_country_code: String,
+    upload_dir: PathBuf,
 }
 
 #[tokio::main]
@@ -79,9 +98,64 @@ async fn main() {
             .expect("ADMIN_SUBNET was not valid")
     };
 
+    let purchase_undo_window = {
+        let seconds = match env::var("PURCHASE_UNDO_WINDOW") {
+            Ok(seconds) => seconds
+                .parse()
+                .expect("PURCHASE_UNDO_WINDOW was not a valid number of seconds"),
+            Err(_) => 300,
+        };
+        chrono::Duration::seconds(seconds)
+    };
+
+    let max_purchase_quantity = match env::var("MAX_PURCHASE_QUANTITY") {
+        Ok(quantity) => quantity
+            .parse()
+            .expect("MAX_PURCHASE_QUANTITY was not a valid number"),
+        Err(_) => 100,
+    };
+
+    let invite_validity = {
+        let seconds = match env::var("INVITE_VALIDITY") {
+            Ok(seconds) => seconds
+                .parse()
+                .expect("INVITE_VALIDITY was not a valid number of seconds"),
+            Err(_) => 7 * 24 * 60 * 60,
+        };
+        chrono::Duration::seconds(seconds)
+    };
+
+    let password_reset_validity = {
+        let seconds = match env::var("PASSWORD_RESET_VALIDITY") {
+            Ok(seconds) => seconds
+                .parse()
+                .expect("PASSWORD_RESET_VALIDITY was not a valid number of seconds"),
+            Err(_) => 24 * 60 * 60,
+        };
+        chrono::Duration::seconds(seconds)
+    };
+
+    let default_country_code =
+        env::var("DEFAULT_COUNTRY_CODE").unwrap_or_else(|_| "351".to_string());
+    assert!(
+        !default_country_code.is_empty()
+            && default_country_code.bytes().all(|b| b.is_ascii_digit()),
+        "DEFAULT_COUNTRY_CODE was not a valid country calling code"
+    );
+
+    fs::create_dir_all(&opt.upload_dir)
+        .await
+        .expect("Unable to create the upload directory");
+
     let config = Config {
         hmac_secret,
         admin_subnet,
+        purchase_undo_window,
+        max_purchase_quantity,
+        invite_validity,
+        password_reset_validity,
+        default_country_code,
+        upload_dir: PathBuf::from(&opt.upload_dir),
     };
 
     let conn = Database::connect(db_url)
@@ -92,24 +166,91 @@ async fn main() {
     let api_routes = Router::new()
         .route("/login", post(user_routes::login))
         .route("/register", post(user_routes::register))
-        .route("/user/info", get(user_routes::user_info))
+        .route(
+            "/user/info",
+            get(user_routes::user_info).patch(user_routes::update_user_info),
+        )
+        .route("/user/password", post(user_routes::change_password))
+        .route("/password-reset", post(user_routes::reset_password))
         .route("/logout", get(user_routes::logout))
+        .route("/invites", get(invite_routes::list))
+        .route("/invite", post(invite_routes::create))
+        .route("/admin/users", get(admin_routes::list_users))
+        .route("/admin/user", post(admin_routes::create_user))
+        .route("/admin/user/:id", put(admin_routes::update_user))
+        .route(
+            "/admin/user/:id/password",
+            post(admin_routes::reset_password),
+        )
+        .route(
+            "/admin/user/:id/password-reset",
+            post(admin_routes::create_password_reset),
+        )
         .route("/products", get(product_routes::list))
+        .route("/products/archived", get(product_routes::list_archived))
+        .route(
+            "/products/barcode/:barcode",
+            get(product_routes::get_by_barcode),
+        )
         .route("/product", post(product_routes::insert))
+        .route("/categories", get(category_routes::list))
+        .route("/category", post(category_routes::insert))
+        .route(
+            "/category/:id",
+            put(category_routes::update).delete(category_routes::delete),
+        )
+        .route(
+            "/product/:id",
+            get(product_routes::get).put(product_routes::update),
+        )
+        .route("/product/:id/history", get(product_routes::price_history))
+        .route("/product/:id/restock", post(product_routes::restock))
+        .route("/product/:id/stock", post(product_routes::adjust_stock))
+        .route("/product/:id/archive", post(product_routes::archive))
+        .route("/product/:id/unarchive", post(product_routes::unarchive))
         .route("/product/:id/purchase", post(product_routes::purchase))
+        .route("/product/:id/image", post(product_routes::upload_image))
+        .route("/checkout", post(product_routes::checkout))
         .route(
             "/purchases/seller-summary",
             get(purchase_routes::seller_summary),
         )
+        .route(
+            "/purchases/buyer-summary",
+            get(purchase_routes::buyer_summary),
+        )
         .route("/purchases/history", get(purchase_routes::purchase_history))
+        .route("/purchase/:id", delete(purchase_routes::cancel_purchase))
         .route("/purchase/:id/pay", post(purchase_routes::pay_purchase))
+        .route("/purchase/:id/unpay", post(purchase_routes::unpay_purchase))
+        .route(
+            "/purchase/:id/refund",
+            post(purchase_routes::refund_purchase),
+        )
         .route(
             "/purchase/user/:id/pay",
             post(purchase_routes::pay_purchase_user_bulk),
-        );
+        )
+        .route(
+            "/purchase/user/:id/payment",
+            post(purchase_routes::record_payment),
+        )
+        .route("/settlements", get(settlement_routes::list))
+        .route("/settlement/:id", get(settlement_routes::receipt))
+        .route("/settlement/:id/revert", post(settlement_routes::revert))
+        .route("/wallets", get(wallet_routes::list))
+        .route("/wallet/:id/top-up", post(wallet_routes::top_up));
 
     let app = Router::new()
         .nest("/api", api_routes)
+        .nest(
+            image_helpers::UPLOADS_PATH,
+            get_service(ServeDir::new(&opt.upload_dir)).handle_error(
+                |err: std::io::Error| async move {
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("error: {err}"))
+                },
+            ),
+        )
         .fallback(get(|req| async move {
             match ServeDir::new(&opt.static_dir).oneshot(req).await {
                 Ok(res) => match res.status() {
@@ -1,44 +1,184 @@
+use std::{cmp::Reverse, collections::HashMap};
+
 use axum::{
-    extract::{self, Path},
+    body::Bytes,
+    extract::{self, ContentLengthLimit, Path, Query},
+    http::{header, HeaderMap},
     Extension, Json,
 };
 use axum_extra::extract::CookieJar;
 use entity::{
+    category,
     product::{self, Entity as Product},
-    purchase, sea_orm,
+    product_price_history, purchase, sea_orm, user,
+};
+use migration::{Alias, Expr};
+use sea_orm::{
+    prelude::*, Condition, ConnectionTrait, DatabaseConnection, FromQueryResult, QueryOrder,
+    QuerySelect, Set, TransactionTrait,
 };
-use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, Set, TransactionTrait};
 
-use crate::{dtos::{ProductDto, PurchaseDto}, Config};
 use crate::errors::AppError;
+use crate::{
+    dtos::{
+        AdjustStockDto, CheckoutDto, CheckoutErrorDto, PaymentMethod, PriceHistoryDto, ProductDto,
+        ProductListQueryDto, ProductSort, PurchaseDto, RestockDto,
+    },
+    image_helpers, ledger_helpers, stock_helpers, Config,
+};
+
+#[derive(FromQueryResult)]
+struct ProductUnitsSold {
+    product: u32,
+    units_sold: i64,
+}
 
 pub(crate) async fn list(
+    Query(query): Query<ProductListQueryDto>,
     Extension(ref conn): Extension<DatabaseConnection>,
 ) -> Result<Json<Vec<ProductDto>>, AppError> {
-    let entities = Product::find()
-        .filter(product::Column::Stock.gt(0))
-        .order_by_desc(product::Column::Stock)
-        .all(conn)
-        .await?;
-    let mut dtos = Vec::with_capacity(entities.len());
-    for entity in entities {
-        dtos.push(ProductDto::from_entity(entity, conn).await?);
+    let mut select = Product::find().filter(product::Column::Archived.eq(false));
+
+    if !query.include_out_of_stock {
+        select = select.filter(product::Column::Stock.gt(0));
     }
-    Ok(Json(dtos))
+    if let Some(search) = trim_optional(query.search) {
+        select = select.filter(
+            Condition::any()
+                .add(product::Column::Name.contains(&escape_like(&search)))
+                .add(product::Column::Description.contains(&escape_like(&search))),
+        );
+    }
+    if let Some(seller_id) = query.seller {
+        select = select.filter(product::Column::Seller.eq(seller_id));
+    }
+    if let Some(category_id) = query.category {
+        select = select.filter(product::Column::Category.eq(category_id));
+    }
+
+    select = match query.sort {
+        ProductSort::Stock | ProductSort::Popularity => {
+            select.order_by_desc(product::Column::Stock)
+        }
+        ProductSort::Price => select.order_by_asc(product::Column::Price),
+        ProductSort::Name => select.order_by_asc(product::Column::Name),
+        ProductSort::Newest => select.order_by_desc(product::Column::Id),
+    };
+
+    let mut entities = select.find_also_related(user::Entity).all(conn).await?;
+
+    // Popularity is the number of items sold, so one purchase of ten counts as much as ten of one
+    if let ProductSort::Popularity = query.sort {
+        let product_ids: Vec<u32> = entities.iter().map(|(product, _)| product.id).collect();
+        let units_sold: HashMap<u32, i64> = purchase::Entity::find()
+            .select_only()
+            .column(purchase::Column::Product)
+            .column_as(
+                // SUM returns a DECIMAL, which doesn't decode into an integer
+                Expr::col(purchase::Column::Quantity)
+                    .sum()
+                    .cast_as(Alias::new("SIGNED")),
+                "units_sold",
+            )
+            .filter(purchase::Column::Product.is_in(product_ids))
+            .group_by(purchase::Column::Product)
+            .into_model::<ProductUnitsSold>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|sold| (sold.product, sold.units_sold))
+            .collect();
+        // stable sort, so products sold equally often stay ordered by stock
+        entities
+            .sort_by_key(|(product, _)| Reverse(units_sold.get(&product.id).copied().unwrap_or(0)));
+    }
+
+    Ok(Json(ProductDto::from_joined(entities)))
 }
 
-pub(crate) async fn insert(
-    extract::Json(product_dto): extract::Json<ProductDto>,
+pub(crate) async fn list_archived(
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
+) -> Result<Json<Vec<ProductDto>>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let entities = Product::find()
+        .filter(product::Column::Seller.eq(seller_id))
+        .filter(product::Column::Archived.eq(true))
+        .order_by_asc(product::Column::Name)
+        .find_also_related(user::Entity)
+        .all(conn)
+        .await?;
+    Ok(Json(ProductDto::from_joined(entities)))
+}
+
+pub(crate) async fn get(
+    Path(product_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
 ) -> Result<Json<ProductDto>, AppError> {
-    let seller_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
-    // validate stock
-    let stock = product_dto.stock;
-    if stock == 0 {
-        return Err(AppError::BadInput("stock must be greater than 0"));
+    let product = Product::find_by_id(product_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchProduct)?;
+
+    Ok(Json(ProductDto::from_entity(product, conn).await?))
+}
+
+pub(crate) async fn get_by_barcode(
+    Path(barcode): Path<String>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+) -> Result<Json<ProductDto>, AppError> {
+    let barcode = validate_barcode(&barcode)?;
+
+    let (product, seller) = Product::find()
+        .filter(product::Column::Barcode.eq(barcode))
+        .filter(product::Column::Archived.eq(false))
+        .find_also_related(user::Entity)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchProduct)?;
+
+    Ok(Json(ProductDto::from_entity_with_seller(
+        product,
+        seller.expect("seller of product must exist"),
+    )))
+}
+
+pub(crate) async fn price_history(
+    Path(product_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<PriceHistoryDto>>, AppError> {
+    Product::find_by_id(product_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchProduct)?;
+
+    let entities = product_price_history::Entity::find()
+        .find_also_related(user::Entity)
+        .filter(product_price_history::Column::Product.eq(product_id))
+        .order_by_desc(product_price_history::Column::Date)
+        .all(conn)
+        .await?;
+    let mut dtos = Vec::with_capacity(entities.len());
+    for (entity, actor) in entities {
+        let actor = actor.expect("actor of price change must exist");
+        dtos.push(PriceHistoryDto::from_entity(entity, actor)?);
     }
+    Ok(Json(dtos))
+}
+
+struct ProductDetails {
+    name: String,
+    description: Option<String>,
+    price: u32,
+    category: Option<u32>,
+    barcode: Option<String>,
+}
+
+fn validate_product_details(product_dto: ProductDto) -> Result<ProductDetails, AppError> {
     // validate price
     let price = product_dto.price;
     if price == 0 {
@@ -50,20 +190,132 @@ pub(crate) async fn insert(
         return Err(AppError::BadInput("name can't be empty"));
     }
     // validate description
-    let description = product_dto.description.and_then(|s| {
+    let description = trim_optional(product_dto.description);
+    // validate barcode
+    let barcode = trim_optional(product_dto.barcode)
+        .map(|barcode| validate_barcode(&barcode))
+        .transpose()?;
+    Ok(ProductDetails {
+        name: name.to_string(),
+        description,
+        price,
+        category: product_dto.category_id,
+        barcode,
+    })
+}
+
+/// Checks that the barcode is a valid EAN-13, i.e. 13 digits where the last one is
+/// the check digit of the others
+fn validate_barcode(barcode: &str) -> Result<String, AppError> {
+    let digits: Vec<u32> = barcode.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digits.len() != 13 || barcode.len() != 13 {
+        return Err(AppError::BadInput(
+            "barcode must be an EAN-13 with 13 digits",
+        ));
+    }
+    // digits are weighted 1 and 3 alternately, starting from the left
+    let sum: u32 = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, digit)| if i % 2 == 0 { *digit } else { digit * 3 })
+        .sum();
+    if (10 - sum % 10) % 10 != digits[12] {
+        return Err(AppError::BadInput("barcode check digit is wrong"));
+    }
+    Ok(barcode.to_string())
+}
+
+/// Makes sure no other product already uses the barcode
+async fn check_barcode_unused<C: ConnectionTrait>(
+    barcode: Option<&str>,
+    product_id: Option<u32>,
+    conn: &C,
+) -> Result<(), AppError> {
+    if let Some(barcode) = barcode {
+        let mut select = Product::find().filter(product::Column::Barcode.eq(barcode));
+        if let Some(product_id) = product_id {
+            select = select.filter(product::Column::Id.ne(product_id));
+        }
+        if select.one(conn).await?.is_some() {
+            return Err(AppError::DuplicateBarcode);
+        }
+    }
+    Ok(())
+}
+
+async fn check_category_exists<C: ConnectionTrait>(
+    category_id: Option<u32>,
+    conn: &C,
+) -> Result<(), AppError> {
+    if let Some(category_id) = category_id {
+        category::Entity::find_by_id(category_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::NoSuchCategory)?;
+    }
+    Ok(())
+}
+
+/// Escapes the LIKE wildcards, so a search for "100%" doesn't match everything starting with "100"
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn trim_optional(value: Option<String>) -> Option<String> {
+    value.and_then(|s| {
         let trimmed = s.trim();
         if !trimmed.is_empty() {
             Some(trimmed.to_string())
         } else {
             None
         }
-    });
+    })
+}
+
+async fn find_own_product<C: ConnectionTrait>(
+    product_id: u32,
+    seller_id: u32,
+    conn: &C,
+) -> Result<product::Model, AppError> {
+    let product = Product::find_by_id(product_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchProduct)?;
+
+    if product.seller != seller_id {
+        return Err(AppError::Forbidden);
+    }
+    Ok(product)
+}
+
+pub(crate) async fn insert(
+    extract::Json(product_dto): extract::Json<ProductDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+    // validate stock
+    let stock = product_dto.stock;
+    if stock == 0 {
+        return Err(AppError::BadInput("stock must be greater than 0"));
+    }
+    let details = validate_product_details(product_dto)?;
+    check_category_exists(details.category, conn).await?;
+    check_barcode_unused(details.barcode.as_deref(), None, conn).await?;
     let product = product::ActiveModel {
-        name: Set(name.to_string()),
-        description: Set(description),
+        name: Set(details.name),
+        description: Set(details.description),
         seller: Set(seller_id),
         stock: Set(stock),
-        price: Set(price),
+        price: Set(details.price),
+        category: Set(details.category),
+        barcode: Set(details.barcode),
         ..Default::default()
     };
 
@@ -74,25 +326,261 @@ pub(crate) async fn insert(
     Ok(Json(new_product_dto))
 }
 
+pub(crate) async fn update(
+    Path(product_id): Path<u32>,
+    extract::Json(product_dto): extract::Json<ProductDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let txn = conn.begin().await?;
+
+    let product = find_own_product(product_id, seller_id, &txn).await?;
+
+    let price_change_reason = trim_optional(product_dto.price_change_reason.clone());
+    let details = validate_product_details(product_dto)?;
+    check_category_exists(details.category, &txn).await?;
+    check_barcode_unused(details.barcode.as_deref(), Some(product.id), &txn).await?;
+
+    if product.price != details.price {
+        let now = chrono::offset::Utc::now();
+        let price_change = product_price_history::ActiveModel {
+            product: Set(product.id),
+            old_price: Set(product.price),
+            new_price: Set(details.price),
+            date: Set(now),
+            actor: Set(seller_id),
+            reason: Set(price_change_reason),
+            ..Default::default()
+        };
+        price_change.insert(&txn).await?;
+    }
+
+    let mut product: product::ActiveModel = product.into();
+    product.name = Set(details.name);
+    product.description = Set(details.description);
+    // stock is left out on purpose, writing back the value the form was loaded with
+    // would undo every purchase made in the meantime
+    product.price = Set(details.price);
+    product.category = Set(details.category);
+    product.barcode = Set(details.barcode);
+
+    let product = product.update(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(ProductDto::from_entity(product, conn).await?))
+}
+
+pub(crate) async fn restock(
+    Path(product_id): Path<u32>,
+    extract::Json(restock_dto): extract::Json<RestockDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    if restock_dto.quantity == 0 {
+        return Err(AppError::BadInput("quantity must be greater than 0"));
+    }
+
+    let txn = conn.begin().await?;
+
+    find_own_product(product_id, seller_id, &txn).await?;
+
+    stock_helpers::return_stock(product_id, restock_dto.quantity, &txn).await?;
+
+    let product = Product::find_by_id(product_id)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchProduct)?;
+
+    txn.commit().await?;
+
+    Ok(Json(ProductDto::from_entity(product, conn).await?))
+}
+
+/// Corrects the stock by a number of items, e.g. to take out expired or stolen ones.
+/// Relative to the current stock, so purchases made since the form was loaded are kept.
+pub(crate) async fn adjust_stock(
+    Path(product_id): Path<u32>,
+    extract::Json(adjust_dto): extract::Json<AdjustStockDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    if adjust_dto.delta == 0 {
+        return Err(AppError::BadInput("delta can't be 0"));
+    }
+
+    let txn = conn.begin().await?;
+
+    find_own_product(product_id, seller_id, &txn).await?;
+
+    stock_helpers::adjust_stock(product_id, adjust_dto.delta, &txn).await?;
+
+    let product = Product::find_by_id(product_id)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchProduct)?;
+
+    txn.commit().await?;
+
+    Ok(Json(ProductDto::from_entity(product, conn).await?))
+}
+
+pub(crate) async fn upload_image(
+    Path(product_id): Path<u32>,
+    headers: HeaderMap,
+    ContentLengthLimit(data): ContentLengthLimit<Bytes, { image_helpers::MAX_IMAGE_SIZE }>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .ok_or(AppError::BadInput("content type is missing"))?;
+    let (format, extension) = image_helpers::image_format(content_type)?;
+
+    let product = find_own_product(product_id, seller_id, conn).await?;
+
+    // a new name for every upload, so browsers don't show a cached old image
+    let image_name = format!(
+        "{}-{}.{}",
+        product.id,
+        chrono::offset::Utc::now().timestamp_millis(),
+        extension
+    );
+    {
+        let upload_dir = config.upload_dir.clone();
+        let image_name = image_name.clone();
+        tokio::task::spawn_blocking(move || {
+            image_helpers::save_image(&data, format, &upload_dir, &image_name)
+        })
+        .await
+        .expect("saving the image must not panic")?;
+    }
+
+    let old_image = product.image.clone();
+    let mut product: product::ActiveModel = product.into();
+    product.image = Set(Some(image_name));
+    let product = product.update(conn).await?;
+
+    if let Some(old_image) = old_image {
+        image_helpers::remove_image(&config.upload_dir, &old_image).await;
+    }
+
+    Ok(Json(ProductDto::from_entity(product, conn).await?))
+}
+
+pub(crate) async fn archive(
+    Path(product_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    set_archived(product_id, true, conn, config, &jar).await
+}
+
+pub(crate) async fn unarchive(
+    Path(product_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    set_archived(product_id, false, conn, config, &jar).await
+}
+
+async fn set_archived(
+    product_id: u32,
+    archived: bool,
+    conn: &DatabaseConnection,
+    config: &Config,
+    jar: &CookieJar,
+) -> Result<Json<ProductDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let txn = conn.begin().await?;
+
+    let product = find_own_product(product_id, seller_id, &txn).await?;
+
+    let mut product: product::ActiveModel = product.into();
+    product.archived = Set(archived);
+
+    let product = product.update(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(ProductDto::from_entity(product, conn).await?))
+}
+
+/// Checks the quantity of a purchase is positive and within the configured limit
+fn validate_quantity(quantity: u32, config: &Config) -> Result<(), AppError> {
+    if quantity == 0 || quantity > config.max_purchase_quantity {
+        return Err(AppError::InvalidQuantity {
+            max: config.max_purchase_quantity,
+        });
+    }
+    Ok(())
+}
+
+/// Makes sure the price of buying that many items still fits the amounts we store
+fn check_total_price(product: &product::Model, quantity: u32) -> Result<(), AppError> {
+    if product.price.checked_mul(quantity).is_none() {
+        return Err(AppError::BadInput("total price is too large"));
+    }
+    Ok(())
+}
+
 pub(crate) async fn purchase(
     Path(product_id): Path<u32>,
     Json(purchase_dto): Json<PurchaseDto>,
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
-) -> Result<(), AppError> {
-    let buyer_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
+) -> Result<Json<PurchaseDto>, AppError> {
+    let buyer_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    validate_quantity(purchase_dto.quantity, config)?;
 
     let txn = conn.begin().await?;
 
+    // the purchase may be paid from credit, see `ledger_helpers::lock_buyer`
+    ledger_helpers::lock_buyer(buyer_id, &txn).await?;
+
     let product = Product::find_by_id(product_id)
         .one(&txn)
         .await?
         .ok_or(AppError::NoSuchProduct)?;
 
-    if product.stock < purchase_dto.quantity {
-        return Err(AppError::NotEnoughStock);
+    if product.archived {
+        return Err(AppError::ProductArchived);
     }
+    check_total_price(&product, purchase_dto.quantity)?;
+
+    // lock the product row before the purchase row is inserted, inserting it first would
+    // take a shared lock on the product that concurrent purchases then deadlock upgrading
+    stock_helpers::take_stock(product.id, purchase_dto.quantity, &txn).await?;
 
     let now = chrono::offset::Utc::now();
 
@@ -104,14 +592,358 @@ pub(crate) async fn purchase(
         date: Set(now),
         ..Default::default()
     };
-    purchase.insert(&txn).await?;
+    let purchase = purchase.insert(&txn).await?;
+    let seller_id = product.seller;
 
-    let mut product: product::ActiveModel = product.into();
-    product.stock =
-        Set(product.stock.take().expect("product must have stock") - purchase_dto.quantity);
+    // pay straight away if the buyer has enough credit with the seller
+    let purchase =
+        match ledger_helpers::apply_credit(buyer_id, seller_id, PaymentMethod::Credit, &txn).await?
+        {
+            Some(_) => purchase::Entity::find_by_id(purchase.id)
+                .one(&txn)
+                .await?
+                .ok_or(AppError::NoSuchPurchase)?,
+            None => purchase,
+        };
+
+    txn.commit().await?;
+
+    Ok(Json(PurchaseDto::from_entity(purchase, conn).await?))
+}
 
-    product.update(&txn).await?;
+pub(crate) async fn checkout(
+    Json(checkout_dto): Json<CheckoutDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<Vec<PurchaseDto>>, AppError> {
+    let buyer_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    if checkout_dto.items.is_empty() {
+        return Err(AppError::BadInput("checkout must have at least one item"));
+    }
+    // the same product may be in several lines, buy it all at once
+    let mut quantities: Vec<(u32, u32)> = Vec::with_capacity(checkout_dto.items.len());
+    for item in checkout_dto.items {
+        validate_quantity(item.quantity, config)?;
+        match quantities
+            .iter_mut()
+            .find(|(product_id, _)| *product_id == item.product_id)
+        {
+            Some((_, quantity)) => *quantity = quantity.saturating_add(item.quantity),
+            None => quantities.push((item.product_id, item.quantity)),
+        }
+    }
+    for (_, quantity) in &quantities {
+        validate_quantity(*quantity, config)?;
+    }
+
+    let txn = conn.begin().await?;
+
+    // the purchases may be paid from credit, see `ledger_helpers::lock_buyer`
+    ledger_helpers::lock_buyer(buyer_id, &txn).await?;
+
+    let products: HashMap<u32, product::Model> = Product::find()
+        .filter(product::Column::Id.is_in(quantities.iter().map(|(id, _)| *id).collect::<Vec<_>>()))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|product| (product.id, product))
+        .collect();
+
+    let errors: Vec<CheckoutErrorDto> = quantities
+        .iter()
+        .filter_map(|(product_id, quantity)| {
+            let (message, stock) = match products.get(product_id) {
+                None => ("no such product", None),
+                Some(product) if product.archived => ("product has been archived", None),
+                Some(product) if check_total_price(product, *quantity).is_err() => {
+                    ("total price is too large", None)
+                }
+                Some(product) if product.stock < *quantity => {
+                    ("not enough stock", Some(product.stock))
+                }
+                Some(_) => return None,
+            };
+            Some(CheckoutErrorDto {
+                product_id: *product_id,
+                message,
+                stock,
+            })
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(AppError::CheckoutFailed(errors));
+    }
+
+    // always lock the products in the same order, so concurrent checkouts can't deadlock
+    quantities.sort_unstable_by_key(|(product_id, _)| *product_id);
+    for (product_id, quantity) in &quantities {
+        match stock_helpers::take_stock(*product_id, *quantity, &txn).await {
+            Ok(()) => {}
+            // someone else bought it after the stock was checked above
+            Err(AppError::NotEnoughStock) => {
+                return Err(AppError::CheckoutFailed(vec![CheckoutErrorDto {
+                    product_id: *product_id,
+                    message: "not enough stock",
+                    stock: None,
+                }]))
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    let now = chrono::offset::Utc::now();
+    let mut purchase_ids = Vec::with_capacity(quantities.len());
+    let mut seller_ids = Vec::new();
+    for (product_id, quantity) in quantities {
+        let product = &products[&product_id];
+
+        let purchase = purchase::ActiveModel {
+            buyer: Set(buyer_id),
+            product: Set(product.id),
+            quantity: Set(quantity),
+            unit_price: Set(product.price),
+            date: Set(now),
+            ..Default::default()
+        };
+        purchase_ids.push(purchase.insert(&txn).await?.id);
+        if !seller_ids.contains(&product.seller) {
+            seller_ids.push(product.seller);
+        }
+    }
+
+    // pay straight away whatever the buyer has enough credit for
+    for seller_id in seller_ids {
+        ledger_helpers::apply_credit(buyer_id, seller_id, PaymentMethod::Credit, &txn).await?;
+    }
+
+    let purchases = purchase::Entity::find()
+        .filter(purchase::Column::Id.is_in(purchase_ids))
+        .order_by_asc(purchase::Column::Id)
+        .all(&txn)
+        .await?;
 
     txn.commit().await?;
-    Ok(())
+
+    Ok(Json(PurchaseDto::from_entities(purchases, conn).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use axum::{
+        extract::{FromRequest, RequestParts},
+        http::Request,
+    };
+    use migration::{Migrator, MigratorTrait};
+    use sea_orm::{Database, PaginatorTrait};
+
+    use super::*;
+
+    const STOCK: u32 = 10;
+    const BUYERS: usize = 50;
+    const PRICE: u32 = 100;
+    const PAID_PURCHASES: u32 = 5;
+
+    /// Keeps the phone numbers of users created in the same run apart
+    static NEXT_USER: AtomicU32 = AtomicU32::new(0);
+
+    async fn connect() -> (DatabaseConnection, Config) {
+        dotenv::dotenv().ok();
+        let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let conn = Database::connect(db_url)
+            .await
+            .expect("Database connection failed");
+        Migrator::up(&conn, None).await.unwrap();
+
+        let config = Config {
+            hmac_secret: b"test-secret".to_vec().into(),
+            admin_subnet: "127.0.0.1/32".parse().unwrap(),
+            purchase_undo_window: chrono::Duration::seconds(300),
+            max_purchase_quantity: 100,
+            invite_validity: chrono::Duration::days(7),
+            password_reset_validity: chrono::Duration::days(1),
+            default_country_code: "351".to_string(),
+            upload_dir: std::env::temp_dir(),
+        };
+        (conn, config)
+    }
+
+    async fn insert_user(name: &str, conn: &DatabaseConnection) -> user::Model {
+        let phone_number = format!(
+            "+3519{:08}",
+            (chrono::offset::Utc::now().timestamp_subsec_nanos()
+                + NEXT_USER.fetch_add(1, Ordering::Relaxed))
+                % 100_000_000
+        );
+        user::ActiveModel {
+            name: Set(name.to_string()),
+            phone_number: Set(phone_number),
+            hashed_password: Set(String::new()),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await
+        .unwrap()
+    }
+
+    async fn insert_product(seller_id: u32, stock: u32, conn: &DatabaseConnection) -> u32 {
+        product::ActiveModel {
+            name: Set("Last cans".to_string()),
+            seller: Set(seller_id),
+            stock: Set(stock),
+            price: Set(PRICE),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await
+        .unwrap()
+        .id
+    }
+
+    /// Logs the user in through the same cookie extractor a real request goes through
+    async fn login_jar(user_id: u32, config: &Config) -> CookieJar {
+        let cookie = crate::jwt_helpers::new_cookie(user_id, &config.hmac_secret)
+            .unwrap_or_else(|_| panic!("cookie must be signed"));
+        let request = Request::builder()
+            .header(header::COOKIE, cookie.to_string())
+            .body(())
+            .unwrap();
+        CookieJar::from_request(&mut RequestParts::new(request))
+            .await
+            .unwrap()
+    }
+
+    /// Fires one purchase request per buyer at the handler all at once
+    async fn purchase_concurrently(
+        buyer_ids: Vec<u32>,
+        product_id: u32,
+        conn: &DatabaseConnection,
+        config: &Config,
+    ) -> Vec<Result<Json<PurchaseDto>, AppError>> {
+        let handles: Vec<_> = buyer_ids
+            .into_iter()
+            .map(|buyer_id| {
+                let conn = conn.clone();
+                let config = config.clone();
+                tokio::spawn(async move {
+                    let jar = login_jar(buyer_id, &config).await;
+                    purchase(
+                        Path(product_id),
+                        Json(PurchaseDto {
+                            id: None,
+                            buyer: None,
+                            product: None,
+                            quantity: 1,
+                            unit_price: None,
+                            date: None,
+                            paid_date: None,
+                            refunds: Vec::new(),
+                        }),
+                        Extension(conn),
+                        Extension(config),
+                        jar,
+                    )
+                    .await
+                })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            results.push(handle.await.unwrap());
+        }
+        results
+    }
+
+    /// Many buyers racing for the last items must never take more than the stock
+    #[tokio::test]
+    #[ignore = "needs a MariaDB database in DATABASE_URL"]
+    async fn concurrent_purchases_do_not_oversell() {
+        let (conn, config) = connect().await;
+        let user = insert_user("Stock race", &conn).await;
+        let product_id = insert_product(user.id, STOCK, &conn).await;
+
+        let results =
+            purchase_concurrently(vec![user.id; BUYERS], product_id, &conn, &config).await;
+
+        let mut bought = 0;
+        for result in results {
+            match result {
+                Ok(_) => bought += 1,
+                Err(AppError::NotEnoughStock) => {}
+                Err(_) => panic!("purchase failed for a reason other than stock"),
+            }
+        }
+
+        let product = Product::find_by_id(product_id)
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bought, STOCK);
+        assert_eq!(product.stock, 0);
+        let purchased: u32 = purchase::Entity::find()
+            .filter(purchase::Column::Product.eq(product_id))
+            .all(&conn)
+            .await
+            .unwrap()
+            .iter()
+            .map(|purchase| purchase.quantity)
+            .sum();
+        assert_eq!(purchased, STOCK);
+    }
+
+    /// Purchases racing to be paid from the same credit must never spend more than there is
+    #[tokio::test]
+    #[ignore = "needs a MariaDB database in DATABASE_URL"]
+    async fn concurrent_purchases_do_not_overspend_credit() {
+        let (conn, config) = connect().await;
+        let seller = insert_user("Credit seller", &conn).await;
+        let buyer = insert_user("Credit race", &conn).await;
+        let product_id = insert_product(seller.id, BUYERS as u32, &conn).await;
+        ledger_helpers::record_payment(
+            buyer.id,
+            seller.id,
+            PAID_PURCHASES * PRICE,
+            PaymentMethod::Cash,
+            &conn,
+        )
+        .await
+        .unwrap_or_else(|_| panic!("payment must be recorded"));
+
+        let results =
+            purchase_concurrently(vec![buyer.id; BUYERS], product_id, &conn, &config).await;
+
+        let mut paid = 0;
+        for result in results {
+            let Json(purchase) = result.unwrap_or_else(|_| panic!("purchase must succeed"));
+            if purchase.paid_date.is_some() {
+                paid += 1;
+            }
+        }
+
+        assert_eq!(paid, PAID_PURCHASES);
+        let unpaid = ledger_helpers::unpaid_purchases(buyer.id, seller.id)
+            .count(&conn)
+            .await
+            .unwrap();
+        assert_eq!(unpaid, BUYERS - PAID_PURCHASES as usize);
+        let balance = ledger_helpers::credit_balance(buyer.id, seller.id, &conn)
+            .await
+            .unwrap_or_else(|_| panic!("balance must be readable"));
+        assert_eq!(balance, 0);
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards() {
+        assert_eq!(escape_like("100% juice"), "100\\% juice");
+        assert_eq!(escape_like("snake_case"), "snake\\_case");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+        assert_eq!(escape_like("cola"), "cola");
+    }
 }
@@ -1,30 +1,50 @@
 use std::collections::HashMap;
 
 use axum::{
-    extract::{self, Path},
+    extract::{self, Path, Query},
     Extension, Json,
 };
 use axum_extra::extract::CookieJar;
 use entity::{
     product,
     purchase::{self, Entity as Purchase},
-    sea_orm, user,
+    refund, sea_orm,
+    settlement::{self, Entity as Settlement},
+    user,
 };
-use migration::{Expr, Query};
+use migration::Expr;
 use sea_orm::{
-    prelude::*, DatabaseConnection, FromQueryResult, JoinType, QueryOrder, QuerySelect, Set,
-    TransactionTrait, Unchanged,
+    prelude::*, Condition, ConnectionTrait, DatabaseConnection, FromQueryResult, JoinType,
+    PaginatorTrait, QueryOrder, QuerySelect, Set, TransactionTrait,
 };
 
-use crate::{dtos::{BuyerGroupedPurchasesDto, PayPurchaseUserBulkDto, PurchaseDto}, Config};
 use crate::errors::AppError;
+use crate::{
+    dtos::{
+        BuyerGroupedPurchasesDto, PayPurchaseDto, PayPurchaseUserBulkDto, PaymentMethod,
+        PurchaseCursorDto, PurchaseDto, PurchaseHistoryPageDto, PurchaseHistoryQueryDto,
+        RecordPaymentDto, RefundPurchaseDto, SellerGroupedPurchasesDto, SettlementDto, UserDto,
+    },
+    ledger_helpers, stock_helpers, Config,
+};
+
+const PURCHASE_HISTORY_PAGE_SIZE: u64 = 20;
+const PURCHASE_HISTORY_MAX_PAGE_SIZE: u64 = 100;
+
+/// What is left to pay for purchases worth `total` once the credit is used up,
+/// a negative credit is owed on top of the purchases
+fn amount_due(total: u32, credit: i64) -> u32 {
+    u32::try_from((i64::from(total) - credit).max(0)).unwrap_or(u32::MAX)
+}
 
 pub(crate) async fn seller_summary(
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
 ) -> Result<Json<Vec<BuyerGroupedPurchasesDto>>, AppError> {
-    let seller_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
 
     // Sold products
     let entities = Purchase::find()
@@ -34,31 +54,28 @@ pub(crate) async fn seller_summary(
         .order_by_desc(purchase::Column::Date)
         .all(conn)
         .await?;
-    let mut dtos = Vec::with_capacity(entities.len());
-    // FIXME: converting this model to DTO will trigger 3 SQL queries for each entity
-    for entity in entities {
-        dtos.push(PurchaseDto::from_entity(entity, conn).await?);
-    }
+    let dtos = PurchaseDto::from_entities(entities, conn).await?;
+
+    let mut credits = ledger_helpers::seller_credit_balances(seller_id, conn).await?;
 
     // Grouped amount due and purchases per user
-    let buyer_grouped_purchases: Vec<BuyerGroupedPurchasesDto> = dtos
+    let mut buyer_grouped_purchases: Vec<BuyerGroupedPurchasesDto> = dtos
         .into_iter()
         .fold(
             HashMap::new(),
             |mut acc: HashMap<u32, Vec<PurchaseDto>>, purchase| {
                 let buyer_id = purchase.buyer.as_ref().expect("buyer must exist").id;
-                acc.entry(buyer_id).or_insert(Vec::new()).push(purchase);
+                acc.entry(buyer_id).or_default().push(purchase);
                 acc
             },
         )
         .into_iter()
-        .map(|(_, buyer_purchases)| {
-            let amount_due: u32 = buyer_purchases
+        .map(|(buyer_id, buyer_purchases)| {
+            let total: u32 = buyer_purchases
                 .iter()
-                .map(|purchase| {
-                    purchase.quantity * purchase.unit_price.expect("purchase must have unit price")
-                })
+                .map(|purchase| purchase.amount_due())
                 .sum();
+            let credit = credits.remove(&buyer_id).unwrap_or_default();
 
             BuyerGroupedPurchasesDto {
                 buyer: buyer_purchases
@@ -68,79 +85,453 @@ pub(crate) async fn seller_summary(
                     .as_ref()
                     .expect("buyer must exist")
                     .clone(),
-                amount_due,
+                amount_due: amount_due(total, credit),
+                credit,
                 purchases: buyer_purchases,
             }
         })
         .collect();
 
+    // Buyers without unpaid purchases, but with credit or debt carried over
+    let buyers = user::Entity::find()
+        .filter(user::Column::Id.is_in(credits.keys().copied().collect::<Vec<_>>()))
+        .all(conn)
+        .await?;
+    for buyer in buyers {
+        buyer_grouped_purchases.push(BuyerGroupedPurchasesDto {
+            amount_due: amount_due(0, credits[&buyer.id]),
+            credit: credits[&buyer.id],
+            buyer: UserDto::from_entity(buyer)?,
+            purchases: Vec::new(),
+        });
+    }
+
     Ok(Json(buyer_grouped_purchases))
 }
 
-pub(crate) async fn purchase_history(
+pub(crate) async fn buyer_summary(
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
-) -> Result<Json<Vec<PurchaseDto>>, AppError> {
-    let buyer_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
+) -> Result<Json<Vec<SellerGroupedPurchasesDto>>, AppError> {
+    let buyer_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
 
+    // Bought products
     let entities = Purchase::find()
-        .join(JoinType::InnerJoin, purchase::Relation::User.def())
-        .filter(user::Column::Id.eq(buyer_id))
+        .filter(purchase::Column::Buyer.eq(buyer_id))
+        .filter(purchase::Column::PaidDate.is_null())
         .order_by_desc(purchase::Column::Date)
         .all(conn)
         .await?;
-    let mut dtos = Vec::with_capacity(entities.len());
-    // FIXME: converting this model to DTO will trigger 3 SQL queries for each entity
-    for entity in entities {
-        dtos.push(PurchaseDto::from_entity(entity, conn).await?);
+    let dtos = PurchaseDto::from_entities(entities, conn).await?;
+
+    // Purchases per seller
+    let mut seller_purchases: HashMap<u32, Vec<PurchaseDto>> = HashMap::new();
+    for purchase in dtos {
+        let seller_id = purchase
+            .product
+            .as_ref()
+            .and_then(|product| product.seller_id)
+            .expect("product must have a seller");
+        seller_purchases
+            .entry(seller_id)
+            .or_default()
+            .push(purchase);
+    }
+    let credits = ledger_helpers::buyer_credit_balances(buyer_id, conn).await?;
+
+    let seller_ids: Vec<u32> = seller_purchases
+        .keys()
+        .chain(credits.keys())
+        .copied()
+        .collect();
+    let sellers = user::Entity::find()
+        .filter(user::Column::Id.is_in(seller_ids))
+        .all(conn)
+        .await?;
+
+    let mut seller_grouped_purchases = Vec::with_capacity(sellers.len());
+    for seller in sellers {
+        let purchases = seller_purchases.remove(&seller.id).unwrap_or_default();
+        let total: u32 = purchases.iter().map(|purchase| purchase.amount_due()).sum();
+        let credit = credits.get(&seller.id).copied().unwrap_or_default();
+
+        seller_grouped_purchases.push(SellerGroupedPurchasesDto {
+            seller: UserDto::from_entity(seller)?,
+            amount_due: amount_due(total, credit),
+            credit,
+            purchases,
+        });
     }
-    Ok(Json(dtos))
+
+    Ok(Json(seller_grouped_purchases))
 }
 
-#[derive(Debug, FromQueryResult)]
-struct PurchaseWithSeller {
-    id: u32,
-    seller_id: u32,
-    paid_date: Option<DateTimeUtc>,
+pub(crate) async fn purchase_history(
+    Query(query): Query<PurchaseHistoryQueryDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<PurchaseHistoryPageDto>, AppError> {
+    let buyer_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let limit = query
+        .limit
+        .unwrap_or(PURCHASE_HISTORY_PAGE_SIZE)
+        .clamp(1, PURCHASE_HISTORY_MAX_PAGE_SIZE);
+
+    let mut select = Purchase::find()
+        .join(JoinType::InnerJoin, purchase::Relation::Product.def())
+        .filter(purchase::Column::Buyer.eq(buyer_id));
+
+    // Continue after the last purchase of the previous page
+    match (query.before_date, query.before_id) {
+        (Some(before_date), Some(before_id)) => {
+            select = select.filter(
+                Condition::any()
+                    .add(purchase::Column::Date.lt(before_date))
+                    .add(
+                        Condition::all()
+                            .add(purchase::Column::Date.eq(before_date))
+                            .add(purchase::Column::Id.lt(before_id)),
+                    ),
+            );
+        }
+        (None, None) => {}
+        _ => {
+            return Err(AppError::BadInput(
+                "before_date and before_id must be given together",
+            ))
+        }
+    }
+
+    if let Some(from) = query.from {
+        select = select.filter(purchase::Column::Date.gte(from));
+    }
+    if let Some(to) = query.to {
+        select = select.filter(purchase::Column::Date.lt(to));
+    }
+    if let Some(product_id) = query.product {
+        select = select.filter(purchase::Column::Product.eq(product_id));
+    }
+    if let Some(seller_id) = query.seller {
+        select = select.filter(product::Column::Seller.eq(seller_id));
+    }
+    match query.paid {
+        Some(true) => select = select.filter(purchase::Column::PaidDate.is_not_null()),
+        Some(false) => select = select.filter(purchase::Column::PaidDate.is_null()),
+        None => {}
+    }
+
+    // Fetch one more than requested to know if there is another page
+    let mut entities = select
+        .order_by_desc(purchase::Column::Date)
+        .order_by_desc(purchase::Column::Id)
+        .limit(limit + 1)
+        .all(conn)
+        .await?;
+    let next_cursor = if entities.len() as u64 > limit {
+        entities.truncate(limit as usize);
+        entities.last().map(|last| PurchaseCursorDto {
+            before_date: last.date,
+            before_id: last.id,
+        })
+    } else {
+        None
+    };
+
+    Ok(Json(PurchaseHistoryPageDto {
+        purchases: PurchaseDto::from_entities(entities, conn).await?,
+        next_cursor,
+    }))
 }
 
-pub(crate) async fn pay_purchase(
+pub(crate) async fn cancel_purchase(
     Path(purchase_id): Path<u32>,
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
 ) -> Result<(), AppError> {
-    let seller_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
+    let buyer_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
 
     let txn = conn.begin().await?;
 
+    // an undo can give credit back, so it waits for whatever is spending it
+    ledger_helpers::lock_buyer(buyer_id, &txn).await?;
+
+    let (purchase, product) = Purchase::find_by_id(purchase_id)
+        .find_also_related(product::Entity)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchPurchase)?;
+    let product = product.expect("product of purchase must exist");
+
+    if purchase.buyer != buyer_id {
+        return Err(AppError::Forbidden);
+    }
+    let now = chrono::offset::Utc::now();
+    if now - purchase.date > config.purchase_undo_window {
+        return Err(AppError::UndoWindowExpired);
+    }
+
+    // a purchase paid from credit as it was made can still be undone, the credit is given back
+    let settlement = match (purchase.paid_date, purchase.settlement) {
+        (None, _) => None,
+        (Some(_), Some(settlement_id)) => {
+            let settlement = Settlement::find_by_id(settlement_id)
+                .one(&txn)
+                .await?
+                .ok_or(AppError::NoSuchSettlement)?;
+            if settlement.credit_used < settlement.amount {
+                return Err(AppError::PurchaseAlreadyPaid);
+            }
+            let refunded = refunded_amount(vec![purchase.id], false, &txn).await?;
+            let amount = (purchase.quantity * purchase.unit_price).saturating_sub(refunded);
+            Some((settlement_id, amount))
+        }
+        (Some(_), None) => return Err(AppError::PurchaseAlreadyPaid),
+    };
+
+    // items that have been refunded were already returned to stock
+    let returned_quantity: u32 = purchase
+        .find_related(refund::Entity)
+        .all(&txn)
+        .await?
+        .iter()
+        .map(|refund| refund.returned_quantity)
+        .sum();
+    stock_helpers::return_stock(product.id, purchase.quantity - returned_quantity, &txn).await?;
+
+    // a concurrent undo already deleted it, its stock must not be returned twice
+    let result = purchase.delete(&txn).await?;
+    if result.rows_affected != 1 {
+        return Err(AppError::NoSuchPurchase);
+    }
+
+    if let Some((settlement_id, amount)) = settlement {
+        remove_from_settlement(settlement_id, amount, &txn).await?;
+    }
+
+    txn.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn refund_purchase(
+    Path(purchase_id): Path<u32>,
+    extract::Json(refund_dto): extract::Json<RefundPurchaseDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<PurchaseDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let reason = refund_dto.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::BadInput("reason can't be empty"));
+    }
+    if refund_dto.amount == 0 {
+        return Err(AppError::BadInput("amount must be greater than 0"));
+    }
+    let returned_quantity = refund_dto.returned_quantity.unwrap_or(0);
+
+    // the buyer never changes, so it can be looked up before the transaction and their row
+    // locked first, the refund may be paid out as credit
+    let buyer_id = Purchase::find_by_id(purchase_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchPurchase)?
+        .buyer;
+
+    let txn = conn.begin().await?;
+
+    ledger_helpers::lock_buyer(buyer_id, &txn).await?;
+
+    // concurrent refunds must see each other, or together they could exceed the price
+    let purchase = Purchase::find_by_id(purchase_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchPurchase)?;
+    let product = purchase
+        .find_related(product::Entity)
+        .one(&txn)
+        .await?
+        .expect("product of purchase must exist");
+
+    if product.seller != seller_id {
+        return Err(AppError::Forbidden);
+    }
+
+    let previous_refunds = purchase.find_related(refund::Entity).all(&txn).await?;
+    let refunded_amount: u32 = previous_refunds.iter().map(|refund| refund.amount).sum();
+    let previously_returned: u32 = previous_refunds
+        .iter()
+        .map(|refund| refund.returned_quantity)
+        .sum();
+
+    // a sum that doesn't fit is certainly above the limit, don't let it wrap around below it
+    match refunded_amount.checked_add(refund_dto.amount) {
+        Some(total) if total <= purchase.quantity * purchase.unit_price => {}
+        _ => {
+            return Err(AppError::BadInput(
+                "can't refund more than the price of the purchase",
+            ))
+        }
+    }
+    match previously_returned.checked_add(returned_quantity) {
+        Some(total) if total <= purchase.quantity => {}
+        _ => {
+            return Err(AppError::BadInput(
+                "can't return more items than were purchased",
+            ))
+        }
+    }
+
+    let now = chrono::offset::Utc::now();
+    let refund = refund::ActiveModel {
+        purchase: Set(purchase.id),
+        amount: Set(refund_dto.amount),
+        reason: Set(reason.to_string()),
+        returned_quantity: Set(returned_quantity),
+        date: Set(now),
+        credited: Set(purchase.paid_date.is_some()),
+        ..Default::default()
+    };
+    refund.insert(&txn).await?;
+
+    if returned_quantity > 0 {
+        stock_helpers::return_stock(product.id, returned_quantity, &txn).await?;
+    }
+
+    // the buyer already paid for it, so the refund is owed to them as credit
+    if purchase.paid_date.is_some() {
+        ledger_helpers::apply_credit(purchase.buyer, seller_id, PaymentMethod::Credit, &txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(Json(PurchaseDto::from_entity(purchase, conn).await?))
+}
+
+#[derive(Debug, FromQueryResult)]
+struct PurchaseWithSeller {
+    id: u32,
+    buyer: u32,
+    seller_id: u32,
+    quantity: u32,
+    unit_price: u32,
+    paid_date: Option<DateTimeUtc>,
+    settlement: Option<u32>,
+}
+
+async fn find_purchase_with_seller<C: ConnectionTrait>(
+    purchase_id: u32,
+    seller_id: u32,
+    conn: &C,
+) -> Result<PurchaseWithSeller, AppError> {
     let purchase = Purchase::find_by_id(purchase_id)
         .column_as(product::Column::Seller, "seller_id")
         .join(JoinType::InnerJoin, purchase::Relation::Product.def())
         .into_model::<PurchaseWithSeller>()
-        .one(&txn)
+        .one(conn)
         .await?
         .ok_or(AppError::NoSuchPurchase)?;
 
     if purchase.seller_id != seller_id {
         return Err(AppError::Forbidden);
     }
+    Ok(purchase)
+}
+
+/// Sum of everything that has been refunded on the given purchases,
+/// optionally leaving out the refunds that went to the buyer's credit
+async fn refunded_amount<C: ConnectionTrait>(
+    purchase_ids: Vec<u32>,
+    include_credited: bool,
+    conn: &C,
+) -> Result<u32, AppError> {
+    let mut refunds = refund::Entity::find().filter(refund::Column::Purchase.is_in(purchase_ids));
+    if !include_credited {
+        refunds = refunds.filter(refund::Column::Credited.eq(false));
+    }
+    Ok(refunds
+        .all(conn)
+        .await?
+        .iter()
+        .map(|refund| refund.amount)
+        .sum())
+}
+
+pub(crate) async fn pay_purchase(
+    Path(purchase_id): Path<u32>,
+    pay_dto: Option<extract::Json<PayPurchaseDto>>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<SettlementDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+    let payment_method = pay_dto
+        .map(|extract::Json(pay_dto)| pay_dto.payment_method)
+        .unwrap_or_default();
+
+    // looked up before the transaction, so the buyer's row is the first thing it locks
+    let purchase = find_purchase_with_seller(purchase_id, seller_id, conn).await?;
+
     if purchase.paid_date.is_some() {
         return Err(AppError::PurchaseAlreadyPaid);
     }
 
+    let txn = conn.begin().await?;
+
+    ledger_helpers::lock_buyer(purchase.buyer, &txn).await?;
+
+    let refunded = refunded_amount(vec![purchase.id], true, &txn).await?;
+    let amount = (purchase.quantity * purchase.unit_price).saturating_sub(refunded);
+    let credit = ledger_helpers::spendable_credit(purchase.buyer, seller_id, &txn).await?;
+
     let now = chrono::offset::Utc::now();
-    let purchase = purchase::ActiveModel {
-        id: Unchanged(purchase.id),
+    let settlement = settlement::ActiveModel {
+        seller: Set(seller_id),
+        buyer: Set(purchase.buyer),
+        date: Set(now),
+        amount: Set(amount),
+        payment_method: Set(payment_method.as_str().to_string()),
+        credit_used: Set(credit.min(amount)),
+        ..Default::default()
+    };
+    let settlement = settlement.insert(&txn).await?;
+
+    let purchase_model = purchase::ActiveModel {
         paid_date: Set(Some(now)),
+        settlement: Set(Some(settlement.id)),
         ..Default::default()
     };
 
-    purchase.save(&txn).await?;
+    // only pay it if nobody else did in the meantime
+    let result = Purchase::update_many()
+        .set(purchase_model)
+        .filter(purchase::Column::Id.eq(purchase.id))
+        .filter(purchase::Column::PaidDate.is_null())
+        .exec(&txn)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(AppError::PurchaseAlreadyPaid);
+    }
 
     txn.commit().await?;
-    Ok(())
+    Ok(Json(SettlementDto::from_entity(settlement, conn).await?))
 }
 
 pub(crate) async fn pay_purchase_user_bulk(
@@ -149,29 +540,51 @@ pub(crate) async fn pay_purchase_user_bulk(
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
-) -> Result<(), AppError> {
-    let seller_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
+) -> Result<Json<SettlementDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
 
     let txn = conn.begin().await?;
 
+    ledger_helpers::lock_buyer(buyer_id, &txn).await?;
+
+    let purchases = ledger_helpers::unpaid_purchases(buyer_id, seller_id)
+        .all(&txn)
+        .await?;
+
+    if purchases.len() as u64 != action_dto.count {
+        return Err(AppError::BulkCountMismatch);
+    }
+
+    let amount: u32 = ledger_helpers::net_amounts(&purchases, &txn)
+        .await?
+        .values()
+        .sum();
+    let purchase_ids: Vec<u32> = purchases.iter().map(|purchase| purchase.id).collect();
+    let credit = ledger_helpers::spendable_credit(buyer_id, seller_id, &txn).await?;
+
     let now = chrono::offset::Utc::now();
+    let settlement = settlement::ActiveModel {
+        seller: Set(seller_id),
+        buyer: Set(buyer_id),
+        date: Set(now),
+        amount: Set(amount),
+        payment_method: Set(action_dto.payment_method.as_str().to_string()),
+        credit_used: Set(credit.min(amount)),
+        ..Default::default()
+    };
+    let settlement = settlement.insert(&txn).await?;
+
     let purchase = purchase::ActiveModel {
         paid_date: Set(Some(now)),
+        settlement: Set(Some(settlement.id)),
         ..Default::default()
     };
 
     let result = Purchase::update_many()
         .set(purchase)
-        .filter(purchase::Column::Buyer.eq(buyer_id))
-        .filter(
-            purchase::Column::Product.in_subquery(
-                Query::select()
-                    .expr(Expr::col(product::Column::Id))
-                    .from(product::Entity)
-                    .and_where(Expr::col(product::Column::Seller).eq(seller_id))
-                    .to_owned(),
-            ),
-        )
+        .filter(purchase::Column::Id.is_in(purchase_ids))
         .filter(purchase::Column::PaidDate.is_null())
         .exec(&txn)
         .await?;
@@ -181,5 +594,120 @@ pub(crate) async fn pay_purchase_user_bulk(
     }
 
     txn.commit().await?;
+    Ok(Json(SettlementDto::from_entity(settlement, conn).await?))
+}
+
+pub(crate) async fn unpay_purchase(
+    Path(purchase_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<(), AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let txn = conn.begin().await?;
+
+    let purchase = find_purchase_with_seller(purchase_id, seller_id, &txn).await?;
+
+    let paid_date = purchase.paid_date.ok_or(AppError::PurchaseNotPaid)?;
+
+    // refunds made after the payment went to the buyer's credit instead of the settlement
+    let refunded = refunded_amount(vec![purchase.id], false, &txn).await?;
+    let amount = (purchase.quantity * purchase.unit_price).saturating_sub(refunded);
+
+    let purchase_model = purchase::ActiveModel {
+        paid_date: Set(None),
+        settlement: Set(None),
+        ..Default::default()
+    };
+
+    // only unpay it if nobody else did in the meantime
+    let result = Purchase::update_many()
+        .set(purchase_model)
+        .filter(purchase::Column::Id.eq(purchase.id))
+        .filter(purchase::Column::PaidDate.eq(paid_date))
+        .exec(&txn)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(AppError::PurchaseNotPaid);
+    }
+
+    // with the purchase unpaid its refunds lower the amount due again instead of being credit
+    refund::Entity::update_many()
+        .col_expr(refund::Column::Credited, Expr::value(false))
+        .filter(refund::Column::Purchase.eq(purchase.id))
+        .exec(&txn)
+        .await?;
+
+    if let Some(settlement_id) = purchase.settlement {
+        remove_from_settlement(settlement_id, amount, &txn).await?;
+    }
+
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Takes a purchase worth `amount` out of its settlement, removing the settlement
+/// if this was its last purchase. The purchase must already be detached from it.
+async fn remove_from_settlement<C: ConnectionTrait>(
+    settlement_id: u32,
+    amount: u32,
+    conn: &C,
+) -> Result<(), AppError> {
+    let remaining = Purchase::find()
+        .filter(purchase::Column::Settlement.eq(settlement_id))
+        .count(conn)
+        .await?;
+    if remaining == 0 {
+        Settlement::delete_many()
+            .filter(settlement::Column::Id.eq(settlement_id))
+            .exec(conn)
+            .await?;
+    } else {
+        let settlement = Settlement::find_by_id(settlement_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::NoSuchSettlement)?;
+        let settlement_amount = settlement.amount.saturating_sub(amount);
+        // whatever credit went into the removed purchase is available again
+        let credit_used = settlement.credit_used.min(settlement_amount);
+        let mut settlement: settlement::ActiveModel = settlement.into();
+        settlement.amount = Set(settlement_amount);
+        settlement.credit_used = Set(credit_used);
+        settlement.update(conn).await?;
+    }
     Ok(())
 }
+
+pub(crate) async fn record_payment(
+    Path(buyer_id): Path<u32>,
+    extract::Json(payment_dto): extract::Json<RecordPaymentDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<Option<SettlementDto>>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let txn = conn.begin().await?;
+
+    let settlement = ledger_helpers::record_payment(
+        buyer_id,
+        seller_id,
+        payment_dto.amount,
+        payment_dto.payment_method,
+        &txn,
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(Json(match settlement {
+        Some(settlement) => Some(SettlementDto::from_entity(settlement, conn).await?),
+        None => None,
+    }))
+}
@@ -0,0 +1,111 @@
+use axum::{extract::Path, Extension, Json};
+use axum_extra::extract::CookieJar;
+use entity::{
+    purchase::{self, Entity as Purchase},
+    refund, sea_orm,
+    settlement::{self, Entity as Settlement},
+};
+use migration::{Expr, Query};
+use sea_orm::{prelude::*, Condition, DatabaseConnection, QueryOrder, Set, TransactionTrait};
+
+use crate::errors::AppError;
+use crate::{dtos::SettlementDto, Config};
+
+pub(crate) async fn list(
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<Vec<SettlementDto>>, AppError> {
+    let user_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let entities = Settlement::find()
+        .filter(
+            Condition::any()
+                .add(settlement::Column::Seller.eq(user_id))
+                .add(settlement::Column::Buyer.eq(user_id)),
+        )
+        .order_by_desc(settlement::Column::Date)
+        .all(conn)
+        .await?;
+    Ok(Json(SettlementDto::from_entities(entities, conn).await?))
+}
+
+pub(crate) async fn receipt(
+    Path(settlement_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<SettlementDto>, AppError> {
+    let user_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let settlement = Settlement::find_by_id(settlement_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NoSuchSettlement)?;
+
+    if settlement.seller != user_id && settlement.buyer != user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(Json(
+        SettlementDto::from_entity_with_purchases(settlement, conn).await?,
+    ))
+}
+
+pub(crate) async fn revert(
+    Path(settlement_id): Path<u32>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<(), AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let txn = conn.begin().await?;
+
+    let settlement = Settlement::find_by_id(settlement_id)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchSettlement)?;
+
+    if settlement.seller != seller_id {
+        return Err(AppError::Forbidden);
+    }
+
+    // with the purchases unpaid their refunds lower the amount due again instead of being credit
+    refund::Entity::update_many()
+        .col_expr(refund::Column::Credited, Expr::value(false))
+        .filter(
+            refund::Column::Purchase.in_subquery(
+                Query::select()
+                    .column(purchase::Column::Id)
+                    .from(Purchase)
+                    .and_where(purchase::Column::Settlement.eq(settlement.id))
+                    .to_owned(),
+            ),
+        )
+        .exec(&txn)
+        .await?;
+
+    let purchase = purchase::ActiveModel {
+        paid_date: Set(None),
+        settlement: Set(None),
+        ..Default::default()
+    };
+
+    Purchase::update_many()
+        .set(purchase)
+        .filter(purchase::Column::Settlement.eq(settlement.id))
+        .exec(&txn)
+        .await?;
+
+    settlement.delete(&txn).await?;
+
+    txn.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use entity::{
+    product::{self, Entity as Product},
+    sea_orm,
+};
+use migration::Expr;
+use sea_orm::{prelude::*, ConnectionTrait};
+
+use crate::errors::AppError;
+
+/// Takes items out of a product's stock.
+/// The check and the subtraction are a single `UPDATE ... WHERE stock >= quantity`,
+/// so concurrent purchases can never take more than there is.
+pub(crate) async fn take_stock<C: ConnectionTrait>(
+    product_id: u32,
+    quantity: u32,
+    conn: &C,
+) -> Result<(), AppError> {
+    let result = Product::update_many()
+        .col_expr(
+            product::Column::Stock,
+            Expr::col(product::Column::Stock).sub(quantity),
+        )
+        .filter(product::Column::Id.eq(product_id))
+        .filter(product::Column::Stock.gte(quantity))
+        .exec(conn)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(AppError::NotEnoughStock);
+    }
+    Ok(())
+}
+
+/// Puts items back into a product's stock, without overwriting concurrent changes to it
+pub(crate) async fn return_stock<C: ConnectionTrait>(
+    product_id: u32,
+    quantity: u32,
+    conn: &C,
+) -> Result<(), AppError> {
+    let result = Product::update_many()
+        .col_expr(
+            product::Column::Stock,
+            Expr::col(product::Column::Stock).add(quantity),
+        )
+        .filter(product::Column::Id.eq(product_id))
+        .filter(product::Column::Stock.lte(u32::MAX - quantity))
+        .exec(conn)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(AppError::BadInput("stock is too large"));
+    }
+    Ok(())
+}
+
+/// Changes a product's stock by `delta` items, which can be negative,
+/// e.g. when items expired or were miscounted
+pub(crate) async fn adjust_stock<C: ConnectionTrait>(
+    product_id: u32,
+    delta: i64,
+    conn: &C,
+) -> Result<(), AppError> {
+    let quantity = u32::try_from(delta.unsigned_abs())
+        .map_err(|_| AppError::BadInput("stock is too large"))?;
+    if delta < 0 {
+        take_stock(product_id, quantity, conn).await
+    } else {
+        return_stock(product_id, quantity, conn).await
+    }
+}
@@ -1,18 +1,26 @@
+use std::net::SocketAddr;
+
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
 };
-use axum::{extract, Extension, Json};
-use axum_client_ip::ClientIp;
+use axum::{
+    extract::{self, ConnectInfo},
+    Extension, Json,
+};
 use axum_extra::extract::cookie::{Cookie, CookieJar};
-use sea_orm::{prelude::*, DatabaseConnection, Set};
+use sea_orm::{prelude::*, ConnectionTrait, DatabaseConnection, Set, TransactionTrait};
 
-use entity::user;
+use entity::{invite, password_reset, user};
+use sha2::{Digest, Sha256};
 
 use crate::{
-    dtos::{LoginDto, RegisterDto, UserDto},
+    dtos::{
+        ChangePasswordDto, LoginDto, RegisterDto, ResetPasswordDto, UpdateUserInfoDto, UserDto,
+        UserRole,
+    },
     errors::AppError,
-    Config,
+    ledger_helpers, Config,
 };
 
 pub(crate) async fn login(
@@ -21,34 +29,77 @@ pub(crate) async fn login(
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
 ) -> Result<CookieJar, AppError> {
+    // a number that can't be normalized can't belong to anyone
+    let phone_number = normalize_phone_number(&login_dto.phone, &config.default_country_code)
+        .map_err(|_| AppError::LoginError)?;
     let user = user::Entity::find()
-        .filter(user::Column::PhoneNumber.eq(login_dto.phone))
+        .filter(user::Column::PhoneNumber.eq(phone_number))
         .one(conn)
         .await?
         .ok_or(AppError::LoginError)?;
 
-    let password = login_dto.password;
-
-    let hashed_password = user.hashed_password;
     let password_hash =
-        PasswordHash::new(&hashed_password).expect("saved password hash must be valid");
-    Argon2::default().verify_password(password.as_bytes(), &password_hash)?;
+        PasswordHash::new(&user.hashed_password).expect("saved password hash must be valid");
+    Argon2::default().verify_password(login_dto.password.as_bytes(), &password_hash)?;
+
+    // only tell whether the account is disabled to someone who knows the password
+    if user.disabled {
+        return Err(AppError::UserDisabled);
+    }
 
     let user_cookie = crate::jwt_helpers::new_cookie(user.id, &config.hmac_secret)?;
     Ok(jar.add(user_cookie))
 }
 
 pub(crate) async fn user_info(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
     jar: CookieJar,
 ) -> Result<Json<UserDto>, AppError> {
-    let user_id = crate::jwt_helpers::get_login(&jar, &config.hmac_secret)?;
+    let user = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn).await?;
 
-    let user = user::Entity::find_by_id(user_id)
-        .one(conn)
+    let credit = ledger_helpers::buyer_credit_balances(user.id, conn)
         .await?
-        .ok_or(AppError::NoSuchUser)?;
+        .values()
+        .sum();
+
+    let mut dto = UserDto::from_entity(user)?;
+    dto.credit = Some(credit);
+    // the admin subnet is trusted with admin access, so it can appoint the first admins
+    if config.admin_subnet.contains(addr.ip()) {
+        dto.role = UserRole::Admin;
+    }
+    Ok(Json(dto))
+}
+
+pub(crate) async fn update_user_info(
+    extract::Json(user_dto): extract::Json<UpdateUserInfoDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<UserDto>, AppError> {
+    let txn = conn.begin().await?;
+
+    let user = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, &txn).await?;
+
+    if user_dto.name.is_none() && user_dto.phone_number.is_none() {
+        return Ok(Json(UserDto::from_entity(user)?));
+    }
+
+    let user_id = user.id;
+    let mut user: user::ActiveModel = user.into();
+    if let Some(name) = user_dto.name {
+        user.name = Set(validate_name(&name)?);
+    }
+    if let Some(phone_number) = user_dto.phone_number {
+        let phone_number = normalize_phone_number(&phone_number, &config.default_country_code)?;
+        check_phone_number_unused(&phone_number, Some(user_id), &txn).await?;
+        user.phone_number = Set(phone_number);
+    }
+    let user = user.update(&txn).await?;
+
+    txn.commit().await?;
 
     Ok(Json(UserDto::from_entity(user)?))
 }
@@ -59,53 +110,210 @@ pub(crate) async fn logout(jar: CookieJar) -> CookieJar {
 
 pub(crate) async fn register(
     extract::Json(register_dto): extract::Json<RegisterDto>,
-    ClientIp(ip): ClientIp,
     Extension(ref conn): Extension<DatabaseConnection>,
     Extension(ref config): Extension<Config>,
 ) -> Result<Json<UserDto>, AppError> {
-    if !config.admin_subnet.contains(ip) {
-        return Err(AppError::Forbidden);
+    let name = validate_name(&register_dto.name)?;
+    let phone_number =
+        normalize_phone_number(&register_dto.phone_number, &config.default_country_code)?;
+    validate_password(&register_dto.password)?;
+
+    let invite_code = register_dto.invite_code.trim().to_uppercase();
+
+    let txn = conn.begin().await?;
+
+    let now = chrono::offset::Utc::now();
+    let invite = invite::Entity::find()
+        .filter(invite::Column::Code.eq(invite_code))
+        .filter(invite::Column::UsedBy.is_null())
+        .filter(invite::Column::ExpiryDate.gt(now))
+        .one(&txn)
+        .await?
+        .ok_or(AppError::InvalidInvite)?;
+
+    check_phone_number_unused(&phone_number, None, &txn).await?;
+
+    let user = user::ActiveModel {
+        name: Set(name),
+        phone_number: Set(phone_number),
+        hashed_password: Set(hash_password(&register_dto.password)?),
+        ..Default::default()
+    };
+
+    let user = user.insert(&txn).await?;
+
+    // only succeeds for the first of two registrations racing for the same invite
+    let used = invite::Entity::update_many()
+        .set(invite::ActiveModel {
+            used_by: Set(Some(user.id)),
+            used_date: Set(Some(now)),
+            ..Default::default()
+        })
+        .filter(invite::Column::Id.eq(invite.id))
+        .filter(invite::Column::UsedBy.is_null())
+        .exec(&txn)
+        .await?;
+    if used.rows_affected == 0 {
+        return Err(AppError::InvalidInvite);
+    }
+
+    txn.commit().await?;
+
+    Ok(Json(UserDto::from_entity(user)?))
+}
+
+pub(crate) async fn change_password(
+    extract::Json(password_dto): extract::Json<ChangePasswordDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<(), AppError> {
+    let user = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn).await?;
+
+    let password_hash =
+        PasswordHash::new(&user.hashed_password).expect("saved password hash must be valid");
+    Argon2::default().verify_password(password_dto.old_password.as_bytes(), &password_hash)?;
+
+    validate_password(&password_dto.new_password)?;
+
+    let mut user: user::ActiveModel = user.into();
+    user.hashed_password = Set(hash_password(&password_dto.new_password)?);
+    user.update(conn).await?;
+
+    Ok(())
+}
+
+/// Sets a new password using a one-time token created by an admin
+pub(crate) async fn reset_password(
+    extract::Json(reset_dto): extract::Json<ResetPasswordDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+) -> Result<(), AppError> {
+    validate_password(&reset_dto.password)?;
+
+    let token_hash = hash_reset_token(&reset_dto.token);
+
+    let txn = conn.begin().await?;
+
+    let now = chrono::offset::Utc::now();
+    let reset = password_reset::Entity::find()
+        .filter(password_reset::Column::TokenHash.eq(token_hash))
+        .filter(password_reset::Column::UsedDate.is_null())
+        .filter(password_reset::Column::ExpiryDate.gt(now))
+        .one(&txn)
+        .await?
+        .ok_or(AppError::InvalidResetToken)?;
+
+    // only succeeds for the first of two requests racing for the same token
+    let used = password_reset::Entity::update_many()
+        .set(password_reset::ActiveModel {
+            used_date: Set(Some(now)),
+            ..Default::default()
+        })
+        .filter(password_reset::Column::Id.eq(reset.id))
+        .filter(password_reset::Column::UsedDate.is_null())
+        .exec(&txn)
+        .await?;
+    if used.rows_affected == 0 {
+        return Err(AppError::InvalidResetToken);
     }
 
-    let name = register_dto.name;
+    user::Entity::update_many()
+        .set(user::ActiveModel {
+            hashed_password: Set(hash_password(&reset_dto.password)?),
+            ..Default::default()
+        })
+        .filter(user::Column::Id.eq(reset.user))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(())
+}
+
+/// Only a hash of reset tokens is stored, so a leaked database can't be used to take over accounts.
+/// The tokens are random, so a fast hash is enough.
+pub(crate) fn hash_reset_token(token: &str) -> String {
+    format!(
+        "{:x}",
+        Sha256::digest(token.trim().to_uppercase().as_bytes())
+    )
+}
+
+pub(crate) fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
     if name.is_empty() {
         return Err(AppError::BadInput("name can't be empty"));
     }
     if name.len() > 30 {
         return Err(AppError::BadInput("name can't be longer than 30"));
     }
+    Ok(name.to_string())
+}
+
+/// Turns a phone number into its E.164 form, e.g. `+351912345678`, so the same number
+/// is always saved and looked up the same way.
+/// Numbers without a `+` or `00` international prefix are from the default country.
+pub(crate) fn normalize_phone_number(
+    phone_number: &str,
+    default_country_code: &str,
+) -> Result<String, AppError> {
+    let phone_number: String = phone_number
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
 
-    let phone_number = register_dto.phone_number;
-    if !(phone_number.len() == 9 && phone_number.chars().all(|c| c.is_digit(10))) {
-        return Err(AppError::BadInput("phone number must be 9 digits long"));
+    let digits = if let Some(international) = phone_number.strip_prefix('+') {
+        international.to_string()
+    } else if let Some(international) = phone_number.strip_prefix("00") {
+        international.to_string()
+    } else {
+        // drop the trunk prefix some countries use for national numbers
+        let national = phone_number.strip_prefix('0').unwrap_or(&phone_number);
+        format!("{}{}", default_country_code, national)
+    };
+
+    if !digits.chars().all(|c| c.is_ascii_digit()) || digits.starts_with('0') {
+        return Err(AppError::BadInput(
+            "phone number must be a valid international number",
+        ));
+    }
+    // E.164 numbers have at most 15 digits, and no country has numbers shorter than this
+    if !(8..=15).contains(&digits.len()) {
+        return Err(AppError::BadInput(
+            "phone number must have between 8 and 15 digits",
+        ));
     }
+    Ok(format!("+{}", digits))
+}
 
-    let password = register_dto.password;
+pub(crate) fn validate_password(password: &str) -> Result<(), AppError> {
     if password.len() < 8 {
         return Err(AppError::BadInput("password must be at least 8 characters"));
     }
+    Ok(())
+}
 
-    let user = user::Entity::find()
-        .filter(user::Column::PhoneNumber.eq(phone_number.clone()))
-        .one(conn)
-        .await?;
-    if user.is_some() {
-        return Err(AppError::DuplicateUser);
-    }
-
+pub(crate) fn hash_password(password: &str) -> Result<String, AppError> {
     let salt = SaltString::generate(&mut OsRng);
-    let password_hash = Argon2::default()
+    Ok(Argon2::default()
         .hash_password(password.as_bytes(), &salt)?
-        .to_string();
-
-    let user = user::ActiveModel {
-        name: Set(name.to_string()),
-        phone_number: Set(phone_number),
-        hashed_password: Set(password_hash),
-        ..Default::default()
-    };
-
-    let user = user.insert(conn).await?;
+        .to_string())
+}
 
-    Ok(Json(UserDto::from_entity(user)?))
+/// Makes sure no other user already has the phone number
+pub(crate) async fn check_phone_number_unused<C: ConnectionTrait>(
+    phone_number: &str,
+    user_id: Option<u32>,
+    conn: &C,
+) -> Result<(), AppError> {
+    let mut select = user::Entity::find().filter(user::Column::PhoneNumber.eq(phone_number));
+    if let Some(user_id) = user_id {
+        select = select.filter(user::Column::Id.ne(user_id));
+    }
+    if select.one(conn).await?.is_some() {
+        return Err(AppError::DuplicateUser);
+    }
+    Ok(())
 }
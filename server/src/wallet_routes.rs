@@ -0,0 +1,78 @@
+use axum::{
+    extract::{self, Path},
+    Extension, Json,
+};
+use axum_extra::extract::CookieJar;
+use entity::{sea_orm, user};
+use sea_orm::{prelude::*, DatabaseConnection, TransactionTrait};
+
+use crate::errors::AppError;
+use crate::{
+    dtos::{RecordPaymentDto, UserDto, WalletDto},
+    ledger_helpers, Config,
+};
+
+pub(crate) async fn list(
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<Vec<WalletDto>>, AppError> {
+    let buyer = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn).await?;
+    let balances = ledger_helpers::buyer_credit_balances(buyer.id, conn).await?;
+    let sellers = user::Entity::find()
+        .filter(user::Column::Id.is_in(balances.keys().copied().collect::<Vec<_>>()))
+        .all(conn)
+        .await?;
+
+    let buyer = UserDto::from_entity(buyer)?;
+    let mut dtos = Vec::with_capacity(sellers.len());
+    for seller in sellers {
+        dtos.push(WalletDto {
+            balance: balances[&seller.id],
+            seller: UserDto::from_entity(seller)?,
+            buyer: buyer.clone(),
+        });
+    }
+    Ok(Json(dtos))
+}
+
+pub(crate) async fn top_up(
+    Path(buyer_id): Path<u32>,
+    extract::Json(top_up_dto): extract::Json<RecordPaymentDto>,
+    Extension(ref conn): Extension<DatabaseConnection>,
+    Extension(ref config): Extension<Config>,
+    jar: CookieJar,
+) -> Result<Json<WalletDto>, AppError> {
+    let seller_id = crate::jwt_helpers::get_login_user(&jar, &config.hmac_secret, conn)
+        .await?
+        .id;
+
+    let txn = conn.begin().await?;
+
+    ledger_helpers::record_payment(
+        buyer_id,
+        seller_id,
+        top_up_dto.amount,
+        top_up_dto.payment_method,
+        &txn,
+    )
+    .await?;
+    let balance = ledger_helpers::credit_balance(buyer_id, seller_id, &txn).await?;
+
+    let seller = user::Entity::find_by_id(seller_id)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchUser)?;
+    let buyer = user::Entity::find_by_id(buyer_id)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NoSuchUser)?;
+
+    txn.commit().await?;
+
+    Ok(Json(WalletDto {
+        seller: UserDto::from_entity(seller)?,
+        buyer: UserDto::from_entity(buyer)?,
+        balance,
+    }))
+}